@@ -1,38 +1,121 @@
 extern crate hexdump;
 
-use ::std::{env, fs};
+use ::std::{cmp, env, fmt, fs, process, thread};
+use ::std::collections::VecDeque;
+use ::std::collections::hash_map::RandomState;
+use ::std::hash::{BuildHasher, Hasher};
+use ::std::ops::Range;
 use ::std::path::{PathBuf, Path};
-use ::std::io::{self, stderr, Seek, SeekFrom, Read, Write};
-use ::std::process::{exit};
-use hexdump::hexdump;
+use ::std::io::{self, stderr, stdout, IsTerminal, Seek, SeekFrom, Read, Write};
+use ::std::process::{exit, Command};
+use ::std::sync::Mutex;
+use ::std::time::{Duration, SystemTime, UNIX_EPOCH};
+use hexdump::{hexdump, unhexdump_auto, format_hint, ruler, ruler_for, Charset, ChunkLayout, ColorTheme, DumpFormat, HexDump, ParseError};
 
 //----------------------------------------------------------------
 // All error handling.
 
+// The temporary file of `--edit` while it is being written and edited.
+static TEMP_FILE: Mutex<Option<PathBuf>> = Mutex::new(None);
+
+// Exits with the status, without leaving the temporary file behind.
+fn exit_with(status: i32) -> ! {
+	if let Some(path) = TEMP_FILE.lock().ok().and_then(|mut temp| temp.take()) {
+		let _ = fs::remove_file(path);
+	}
+	exit(status);
+}
+
 fn err_unexpected_end(arg: &str) -> ! {
 	let _ = writeln!(stderr(), "hexdump: {}: unexpected end.", arg);
-	exit(1);
+	exit_with(1);
 }
 fn err_nan(arg: &str) -> ! {
 	let _ = writeln!(stderr(), "hexdump: {}: not a number.", arg);
-	exit(1);
+	exit_with(1);
 }
 fn err_flag(arg: &str) -> ! {
 	let _ = writeln!(stderr(), "hexdump: {}: unknown flag.", arg);
-	exit(1);
+	exit_with(1);
+}
+fn err_invalid(arg: &str, value: &str) -> ! {
+	let _ = writeln!(stderr(), "hexdump: {}: invalid value {:?}.", arg, value);
+	exit_with(1);
 }
 fn err_file_error(err: io::Error, path: &Path) -> ! {
 	let _ = writeln!(stderr(), "hexdump: file error {:?}: {}.", path, err);
-	exit(1);
+	exit_with(1);
+}
+fn err_parse(err: ParseError, path: &Path) -> ! {
+	let _ = writeln!(stderr(), "hexdump: {:?}: {}.", path, err);
+	exit_with(1);
+}
+fn err_output(err: io::Error) -> ! {
+	let _ = writeln!(stderr(), "hexdump: output error: {}.", err);
+	exit_with(1);
+}
+fn err_diff() -> ! {
+	let _ = writeln!(stderr(), "hexdump: --diff: expects two files.");
+	exit_with(1);
+}
+fn err_diff_flag(flag: &str) -> ! {
+	let _ = writeln!(stderr(), "hexdump: --diff: cannot be combined with {}.", flag);
+	exit_with(1);
+}
+fn err_goto_skip() -> ! {
+	let _ = writeln!(stderr(), "hexdump: --goto: cannot be combined with -s.");
+	exit_with(1);
+}
+fn err_terminal() -> ! {
+	let _ = writeln!(stderr(), "hexdump: refusing to write binary to a terminal, use -o FILE or --force.");
+	exit_with(1);
+}
+fn err_editor(err: io::Error, editor: &str) -> ! {
+	let _ = writeln!(stderr(), "hexdump: editor error {:?}: {}.", editor, err);
+	exit_with(1);
+}
+#[cfg(feature = "sha256")]
+fn err_digest(path: &Path, expected: &[u8; 32], actual: &[u8; 32]) -> ! {
+	let _ = writeln!(stderr(), "hexdump: {:?}: sha256 mismatch, expected {} but found {}.", path, sha256::hex(expected), sha256::hex(actual));
+	exit_with(1);
 }
 
 //----------------------------------------------------------------
 // Parse the command line arguments.
 
 
+// Source code forms of the bytes for `--format`.
+#[derive(Copy, Clone)]
+enum Source {
+	RustArray,
+	CEmbed,
+}
+
 struct Parameters {
 	length: Option<usize>,
 	skip: Option<usize>,
+	goto: Option<usize>,
+	tail: Option<usize>,
+	concat: bool,
+	marker: bool,
+	diff: bool,
+	raw: bool,
+	format: Option<Source>,
+	chunk: Option<ChunkLayout>,
+	json_lines: bool,
+	charset: Charset,
+	theme: Option<ColorTheme>,
+	edit: bool,
+	watch: bool,
+	reverse: bool,
+	max_bytes: Option<usize>,
+	separator: usize,
+	rule_every: usize,
+	count_bytes: bool,
+	output: Option<PathBuf>,
+	force: bool,
+	#[cfg(feature = "sha256")]
+	expect_sha256: Option<[u8; 32]>,
 	paths: Vec<PathBuf>,
 }
 impl Default for Parameters {
@@ -40,6 +123,28 @@ impl Default for Parameters {
 		let mut params = Parameters {
 			length: None,
 			skip: None,
+			goto: None,
+			tail: None,
+			concat: false,
+			marker: false,
+			diff: false,
+			raw: false,
+			format: None,
+			chunk: None,
+			json_lines: false,
+			charset: Charset::Ascii,
+			theme: None,
+			edit: false,
+			watch: false,
+			reverse: false,
+			max_bytes: None,
+			separator: 1,
+			rule_every: 0,
+			count_bytes: false,
+			output: None,
+			force: false,
+			#[cfg(feature = "sha256")]
+			expect_sha256: None,
 			paths: Vec::new(),
 		};
 
@@ -48,20 +153,135 @@ impl Default for Parameters {
 
 		while let Some(arg) = args.next() {
 			if let Some(arg) = arg.to_str() {
-				if arg.starts_with("-") {
-					match arg.as_ref() {
+				if arg.starts_with("-") && arg != "-" {
+					match arg {
 						"-n" => {
 							params.length = Some(args
 								.next().unwrap_or_else(|| err_unexpected_end(arg))
 								.into_string().unwrap_or_else(|_| err_nan(arg))
 								.parse().unwrap_or_else(|_| err_nan(arg)));
 						},
+						"--length-from" => {
+							let path = PathBuf::from(args.next().unwrap_or_else(|| err_unexpected_end(arg)));
+							let metadata = fs::metadata(&path)
+								.unwrap_or_else(|e| err_file_error(e, &path));
+							params.length = Some(metadata.len() as usize);
+						},
 						"-s" => {
 							params.skip = Some(args
 								.next().unwrap_or_else(|| err_unexpected_end(arg))
 								.into_string().unwrap_or_else(|_| err_nan(arg))
 								.parse().unwrap_or_else(|_| err_nan(arg)));
 						},
+						"--goto" => {
+							let value = args
+								.next().unwrap_or_else(|| err_unexpected_end(arg))
+								.into_string().unwrap_or_else(|_| err_nan(arg));
+							let digits = value.trim_start_matches("0x").trim_start_matches("0X");
+							params.goto = Some(usize::from_str_radix(digits, 16).unwrap_or_else(|_| err_nan(arg)));
+						},
+						"-t" | "--tail" => {
+							params.tail = Some(args
+								.next().unwrap_or_else(|| err_unexpected_end(arg))
+								.into_string().unwrap_or_else(|_| err_nan(arg))
+								.parse().unwrap_or_else(|_| err_nan(arg)));
+						},
+						"--ascii-charset" => {
+							let value = args
+								.next().unwrap_or_else(|| err_unexpected_end(arg))
+								.into_string().unwrap_or_else(|value| err_invalid(arg, &value.to_string_lossy()));
+							params.charset = match value.as_str() {
+								"ascii" => Charset::Ascii,
+								"latin1" => Charset::Latin1,
+								"utf8" => Charset::Utf8,
+								"ebcdic" => Charset::Ebcdic,
+								"caret" => Charset::Caret,
+								"utf16le" => Charset::Utf16Le,
+								"utf16be" => Charset::Utf16Be,
+								"mixedhex" => Charset::MixedHex,
+								_ => err_invalid(arg, &value),
+							};
+						},
+						"--color" => {
+							params.theme.get_or_insert_with(ColorTheme::default);
+						},
+						"--theme" => {
+							let value = args
+								.next().unwrap_or_else(|| err_unexpected_end(arg))
+								.into_string().unwrap_or_else(|value| err_invalid(arg, &value.to_string_lossy()));
+							params.theme = Some(match value.as_str() {
+								"default" => ColorTheme::default(),
+								"solarized" => ColorTheme::SOLARIZED,
+								"mono" => ColorTheme::MONO,
+								_ => err_invalid(arg, &value),
+							});
+						},
+						"--color-zero" | "--color-control" | "--color-printable" | "--color-high" => {
+							let value = args
+								.next().unwrap_or_else(|| err_unexpected_end(arg))
+								.into_string().unwrap_or_else(|value| err_invalid(arg, &value.to_string_lossy()));
+							let rgb = parse_rgb(&value).unwrap_or_else(|| err_invalid(arg, &value));
+							let theme = params.theme.get_or_insert_with(ColorTheme::default);
+							match arg {
+								"--color-zero" => theme.zero = Some(rgb),
+								"--color-control" => theme.control = Some(rgb),
+								"--color-printable" => theme.printable = Some(rgb),
+								_ => theme.high = Some(rgb),
+							}
+						},
+						"--concat" => params.concat = true,
+						"--marker" => params.marker = true,
+						"--diff" => params.diff = true,
+						"--raw" => params.raw = true,
+						"--format" => {
+							let value = args
+								.next().unwrap_or_else(|| err_unexpected_end(arg))
+								.into_string().unwrap_or_else(|value| err_invalid(arg, &value.to_string_lossy()));
+							params.format = match value.as_str() {
+								"hex" => None,
+								"rust-array" => Some(Source::RustArray),
+								"c-embed" => Some(Source::CEmbed),
+								_ => err_invalid(arg, &value),
+							};
+						},
+						"--chunk" => {
+							let value = args
+								.next().unwrap_or_else(|| err_unexpected_end(arg))
+								.into_string().unwrap_or_else(|value| err_invalid(arg, &value.to_string_lossy()));
+							params.chunk = Some(ChunkLayout::parse(&value).unwrap_or_else(|_| err_invalid(arg, &value)));
+						},
+						"--json-lines" => params.json_lines = true,
+						"--separator" => {
+							params.separator = args
+								.next().unwrap_or_else(|| err_unexpected_end(arg))
+								.into_string().unwrap_or_else(|_| err_nan(arg))
+								.parse().unwrap_or_else(|_| err_nan(arg));
+						},
+						"--rule-every" => {
+							params.rule_every = args
+								.next().unwrap_or_else(|| err_unexpected_end(arg))
+								.into_string().unwrap_or_else(|_| err_nan(arg))
+								.parse().unwrap_or_else(|_| err_nan(arg));
+						},
+						"--count-bytes" => params.count_bytes = true,
+						"-e" | "--edit" => params.edit = true,
+						"--watch" => params.watch = true,
+						"-r" => params.reverse = true,
+						"-o" => params.output = Some(PathBuf::from(args.next().unwrap_or_else(|| err_unexpected_end(arg)))),
+						"--force" => params.force = true,
+						"--max-bytes" => {
+							params.max_bytes = Some(args
+								.next().unwrap_or_else(|| err_unexpected_end(arg))
+								.into_string().unwrap_or_else(|_| err_nan(arg))
+								.parse().unwrap_or_else(|_| err_nan(arg)));
+						},
+						#[cfg(feature = "sha256")]
+						"--expect-sha256" => {
+							let value = args
+								.next().unwrap_or_else(|| err_unexpected_end(arg))
+								.into_string().unwrap_or_else(|value| err_invalid(arg, &value.to_string_lossy()));
+							params.expect_sha256 = Some(sha256::parse(&value).unwrap_or_else(|| err_invalid(arg, &value)));
+						},
 						"--" => break,
 						_ => err_flag(arg),
 					}
@@ -73,45 +293,683 @@ impl Default for Parameters {
 		}
 
 		params.paths.extend(args.map(|os_str| os_str.into()));
+		if params.goto.is_some() && params.skip.is_some() {
+			err_goto_skip();
+		}
 		params
 	}
 }
 
+// Parses a color as `r,g,b`.
+fn parse_rgb(value: &str) -> Option<(u8, u8, u8)> {
+	let mut parts = value.split(',').map(|part| part.trim().parse::<u8>());
+	match (parts.next(), parts.next(), parts.next(), parts.next()) {
+		(Some(Ok(r)), Some(Ok(g)), Some(Ok(b)), None) => Some((r, g, b)),
+		_ => None,
+	}
+}
+
 //----------------------------------------------------------------
 // Read from file and dump hex.
 
-fn dump(params: &Parameters, path: &Path) {
-	println!("Hex dump for {:?}:", path);
+// Applies the formatting options to the dump.
+fn configure<'a>(params: &Parameters, dump: HexDump<'a>) -> HexDump<'a> {
+	let dump = dump.charset(params.charset);
+	match params.theme {
+		Some(theme) => dump.colors(theme),
+		None => dump,
+	}
+}
+
+// Reads the selected bytes of the input, returns the offset of the first byte and the bytes.
+// The path `-` reads from the standard input.
+fn read(params: &Parameters, path: &Path) -> (usize, Vec<u8>) {
+	if path == Path::new("-") {
+		let stdin = io::stdin();
+		let mut stdin = stdin.lock();
+		if let Some(tail) = params.tail {
+			return read_tail(&mut stdin, tail)
+				.unwrap_or_else(|e| err_file_error(e, path));
+		}
+		let start = start(params);
+		io::copy(&mut (&mut stdin).take(start as u64), &mut io::sink())
+			.unwrap_or_else(|e| err_file_error(e, path));
+		return (start, read_data(params, &mut stdin, path));
+	}
 
 	let mut file = fs::File::open(path)
 		.unwrap_or_else(|e| err_file_error(e, path));
 
-	if let Some(skip) = params.skip {
-		file.seek(SeekFrom::Current(skip as i64))
-		.unwrap_or_else(|e| err_file_error(e, path));
+	if let Some(tail) = params.tail {
+		// Fall back to reading everything if the file cannot seek
+		return match file.seek(SeekFrom::End(0)) {
+			Ok(len) => {
+				let start = len.saturating_sub(tail as u64);
+				file.seek(SeekFrom::Start(start))
+					.unwrap_or_else(|e| err_file_error(e, path));
+				let mut data = Vec::new();
+				file.read_to_end(&mut data)
+					.unwrap_or_else(|e| err_file_error(e, path));
+				(start as usize, data)
+			},
+			Err(_) => read_tail(&mut file, tail)
+				.unwrap_or_else(|e| err_file_error(e, path)),
+		};
+	}
+
+	let start = start(params);
+	if start > 0 {
+		file.seek(SeekFrom::Start(start as u64))
+			.unwrap_or_else(|e| err_file_error(e, path));
 	}
+	(start, read_data(params, &mut file, path))
+}
+
+// Offset of the first selected byte, by `--goto` or `-s`.
+fn start(params: &Parameters) -> usize {
+	params.goto.or(params.skip).unwrap_or(0)
+}
 
+fn read_data(params: &Parameters, input: &mut dyn Read, path: &Path) -> Vec<u8> {
 	let mut data: Vec<u8> = Vec::new();
 	if let Some(length) = params.length {
 		data.resize(length, 0);
-		file.read_exact(&mut data)
+		input.read_exact(&mut data)
 			.unwrap_or_else(|e| err_file_error(e, path));
 	}
 	else {
-		file.read_to_end(&mut data)
+		input.read_to_end(&mut data)
 			.unwrap_or_else(|e| err_file_error(e, path));
 	}
+	data
+}
+
+// Keeps the last `tail` bytes of a stream in a ring buffer.
+fn read_tail(input: &mut dyn Read, tail: usize) -> io::Result<(usize, Vec<u8>)> {
+	// The ring grows with the input, a huge tail does not allocate up front
+	let mut ring = VecDeque::with_capacity(cmp::min(tail, 64 * 1024));
+	let mut total = 0;
+	let mut chunk = [0u8; 0x1000];
+	loop {
+		let n = match input.read(&mut chunk) {
+			Ok(0) => break,
+			Ok(n) => n,
+			Err(ref e) if e.kind() == io::ErrorKind::Interrupted => continue,
+			Err(e) => return Err(e),
+		};
+		total += n;
+		let bytes = &chunk[n.saturating_sub(tail)..n];
+		let overflow = (ring.len() + bytes.len()).saturating_sub(tail);
+		ring.drain(..overflow);
+		ring.extend(bytes);
+	}
+	Ok((total - ring.len(), ring.into()))
+}
+
+fn dump(params: &Parameters, path: &Path, out: &mut dyn Write) -> io::Result<()> {
+	let (offset, data) = read(params, path);
+	if params.raw {
+		return out.write_all(&data);
+	}
+	if let Some(format) = params.format {
+		write_source(format, &data, out)?;
+		return verify(params, path, &data, out);
+	}
+	if params.json_lines {
+		write_json_lines(params, configure(params, hexdump(&data, offset)), out)?;
+		return verify(params, path, &data, out);
+	}
+
+	writeln!(out, "Hex dump for {:?}:", path)?;
+	let ruler = row_ruler(params);
+	writeln!(out, "{}", ruler)?;
+	if let Some(ref layout) = params.chunk {
+		write_records(params, layout, &data, offset, out)?;
+	}
+	else {
+		write_dump(params, configure(params, hexdump(&data, offset)), offset..offset + data.len(), &mut 0, out)?;
+	}
+	writeln!(out, "{}", ruler)?;
+	verify(params, path, &data, out)
+}
+
+// Dump all the files as one stream, addresses continue across file boundaries.
+// The path `-` reads from the standard input.
+fn dump_concat(params: &Parameters, out: &mut dyn Write) -> io::Result<()> {
+	// Remember where each file ends in the combined stream
+	let mut data: Vec<u8> = Vec::new();
+	let mut bounds = Vec::new();
+	for path in &params.paths {
+		let result = if path == Path::new("-") {
+			io::stdin().lock().read_to_end(&mut data)
+		}
+		else {
+			fs::File::open(path).and_then(|mut file| file.read_to_end(&mut data))
+		};
+		result.unwrap_or_else(|e| err_file_error(e, path));
+		bounds.push(data.len());
+	}
+
+	let start = match params.tail {
+		Some(tail) => data.len().saturating_sub(tail),
+		None => cmp::min(start(params), data.len()),
+	};
+	let end = match params.length {
+		_ if params.tail.is_some() => data.len(),
+		Some(length) if start + length > data.len() => {
+			let err = io::Error::new(io::ErrorKind::UnexpectedEof, "failed to fill whole buffer");
+			err_file_error(err, params.paths.last().unwrap());
+		},
+		Some(length) => start + length,
+		None => data.len(),
+	};
+	if params.raw {
+		return out.write_all(&data[start..end]);
+	}
+	if let Some(format) = params.format {
+		write_source(format, &data[start..end], out)?;
+		return verify(params, params.paths.last().unwrap(), &data[start..end], out);
+	}
+	if params.json_lines {
+		write_json_lines(params, configure(params, hexdump(&data[start..end], start)), out)?;
+		return verify(params, params.paths.last().unwrap(), &data[start..end], out);
+	}
+
+	writeln!(out, "Hex dump for {:?}:", params.paths)?;
+	let ruler = row_ruler(params);
+	writeln!(out, "{}", ruler)?;
+	if let Some(ref layout) = params.chunk {
+		write_records(params, layout, &data[start..end], start, out)?;
+	}
+	else if params.marker {
+		// Split the dump at the file boundaries
+		let mut addr = start;
+		let mut rows = 0;
+		for &bound in &bounds {
+			if bound <= addr {
+				continue;
+			}
+			let stop = cmp::min(bound, end);
+			write_dump(params, configure(params, hexdump(&data[addr..stop], addr)), start..end, &mut rows, out)?;
+			addr = stop;
+			if addr >= end {
+				break;
+			}
+			writeln!(out, "; ---- next file ----")?;
+		}
+	}
+	else {
+		write_dump(params, configure(params, hexdump(&data[start..end], start)), start..end, &mut 0, out)?;
+	}
+	writeln!(out, "{}", ruler)?;
+	verify(params, params.paths.last().unwrap(), &data[start..end], out)
+}
+
+// Ruler as wide as the rows, the records of `--chunk` are rows as wide as the layout and dumps have 16 bytes per row.
+fn row_ruler(params: &Parameters) -> String {
+	ruler_for(params.chunk.as_ref().map_or(16, ChunkLayout::size))
+}
+
+// Writes the dump with a ruler between every `--rule-every` rows,
+// with `--count-bytes` followed by how much of the selected bytes in `range` is dumped so far.
+// The number of `rows` written carries over between the parts of a dump split at the file boundaries.
+fn write_dump(params: &Parameters, dump: HexDump, range: Range<usize>, rows: &mut usize, out: &mut dyn Write) -> io::Result<()> {
+	let every = match params.rule_every {
+		0 if params.count_bytes => 16,
+		0 => return write!(out, "{}", dump),
+		every => every,
+	};
+	let total = range.end - range.start;
+	// Without annotations or squeezing every row is written as a single line
+	for (row, line) in dump.rows().zip(dump.clone().into_lines()) {
+		if *rows > 0 && rows.is_multiple_of(every) {
+			if params.rule_every > 0 {
+				writeln!(out, "{}", ruler())?;
+			}
+			if params.count_bytes {
+				let done = row.address() as usize - range.start;
+				writeln!(out, "; {}% ({}/{})", done * 100 / total, done, total)?;
+			}
+		}
+		*rows += 1;
+		writeln!(out, "{}", line)?;
+	}
+	Ok(())
+}
+
+// Compares two files in lockstep blocks of 16 bytes without reading either completely.
+// The differing rows of both files are written as they are found, matching rows are collapsed.
+// The range options select the same bytes of both files, the other output formats are rejected.
+fn diff(params: &Parameters, out: &mut dyn Write) -> io::Result<()> {
+	let (old_path, new_path) = match params.paths[..] {
+		[ref old, ref new] => (old, new),
+		_ => err_diff(),
+	};
+	let flags = [
+		(params.tail.is_some(), "-t"),
+		(params.concat, "--concat"),
+		(params.raw, "--raw"),
+		(params.format.is_some(), "--format"),
+		(params.chunk.is_some(), "--chunk"),
+		(params.json_lines, "--json-lines"),
+	];
+	if let Some(&(_, flag)) = flags.iter().find(|&&(set, _)| set) {
+		err_diff_flag(flag);
+	}
+	let start = start(params);
+	let limit = params.length.map_or(u64::MAX, |length| length as u64);
+	let open = |path: &Path| {
+		let mut file = fs::File::open(path).unwrap_or_else(|e| err_file_error(e, path));
+		file.seek(SeekFrom::Start(start as u64)).unwrap_or_else(|e| err_file_error(e, path));
+		io::BufReader::new(file).take(limit)
+	};
+	let (mut old, mut new) = (open(old_path), open(new_path));
 
-	println!("--------:----------------------------------------------------+----------------+");
-	print!("{}", hexdump(&data, params.skip.unwrap_or(0)));
-	println!("--------:----------------------------------------------------+----------------+");
+	// The rulers are indented past the signs of the rows
+	writeln!(out, "Diff of {:?} and {:?}:", old_path, new_path)?;
+	writeln!(out, "  {}", ruler())?;
+	let (mut old_row, mut new_row) = ([0u8; 16], [0u8; 16]);
+	let mut address = start as u64;
+	let mut matching = 0u64;
+	loop {
+		// Keep the blocks aligned to the rows
+		let size = 16 - (address % 16) as usize;
+		let old_len = read_block(&mut old, &mut old_row[..size]).unwrap_or_else(|e| err_file_error(e, old_path));
+		let new_len = read_block(&mut new, &mut new_row[..size]).unwrap_or_else(|e| err_file_error(e, new_path));
+		if old_len == 0 && new_len == 0 {
+			break;
+		}
+		// Once the shorter file ends the rows of the longer one are its tail
+		if old_row[..old_len] == new_row[..new_len] {
+			matching += old_len as u64;
+		}
+		else {
+			if matching > 0 {
+				writeln!(out, "; ---- {} matching bytes ----", matching)?;
+				matching = 0;
+			}
+			for &(sign, row) in &[('-', &old_row[..old_len]), ('+', &new_row[..new_len])] {
+				if !row.is_empty() {
+					write!(out, "{} {}", sign, configure(params, hexdump(row, address as usize)))?;
+				}
+			}
+		}
+		address += size as u64;
+	}
+	if matching > 0 {
+		writeln!(out, "; ---- {} matching bytes ----", matching)?;
+	}
+	writeln!(out, "  {}", ruler())
+}
+
+// Fills the block unless the input ends first, returns the number of bytes read.
+fn read_block(input: &mut dyn Read, block: &mut [u8]) -> io::Result<usize> {
+	let mut len = 0;
+	while len < block.len() {
+		match input.read(&mut block[len..]) {
+			Ok(0) => break,
+			Ok(n) => len += n,
+			Err(ref e) if e.kind() == io::ErrorKind::Interrupted => continue,
+			Err(e) => return Err(e),
+		}
+	}
+	Ok(len)
+}
+
+// Writes a row for every record of `--chunk` with its decoded fields, a trailing partial record is only shown in hex.
+// The hex bytes have an extra space every 8 bytes like the rows of a dump.
+fn write_records(params: &Parameters, layout: &ChunkLayout, data: &[u8], offset: usize, out: &mut dyn Write) -> io::Result<()> {
+	let size = layout.size();
+	for (n, record) in data.chunks(size).enumerate() {
+		let address = offset + n * size;
+		write!(out, "{:08X}: ", address)?;
+		for i in 0..size {
+			if i > 0 && i.is_multiple_of(8) {
+				write!(out, " ")?;
+			}
+			match record.get(i) {
+				Some(byte) => write!(out, " {:02X}", byte)?,
+				None => write!(out, "   ")?,
+			}
+		}
+		let glyphs = configure(params, hexdump(record, address)).cells().map(|(_, _, c)| c).collect::<String>();
+		write!(out, "  |{:1$}|", glyphs, size)?;
+		match layout.decode(record) {
+			Some(fields) => writeln!(out, "  {}", fields)?,
+			None => writeln!(out)?,
+		}
+	}
+	Ok(())
+}
+
+// Writes every row as a JSON object on its own line, without the header and rulers.
+fn write_json_lines(params: &Parameters, dump: HexDump, out: &mut dyn Write) -> io::Result<()> {
+	for row in dump.rows() {
+		writeln!(out, "{}", fmt::from_fn(|f| row.write_json(f, params.charset)))?;
+	}
+	Ok(())
+}
+
+// Writes the bytes as a Rust constant or as a C initializer list.
+fn write_source(format: Source, data: &[u8], out: &mut dyn Write) -> io::Result<()> {
+	match format {
+		Source::RustArray => {
+			writeln!(out, "pub const DATA: [u8; {}] = [", data.len())?;
+			write_byte_list(data, "    ", true, out)?;
+			writeln!(out, "];")
+		},
+		Source::CEmbed => write_byte_list(data, "", false, out),
+	}
+}
+
+// Writes the bytes as comma separated hex literals, 12 per line.
+fn write_byte_list(data: &[u8], indent: &str, trailing_comma: bool, out: &mut dyn Write) -> io::Result<()> {
+	let lines = data.len().div_ceil(12);
+	for (i, line) in data.chunks(12).enumerate() {
+		write!(out, "{}", indent)?;
+		for (j, byte) in line.iter().enumerate() {
+			if j > 0 {
+				write!(out, " ")?;
+			}
+			write!(out, "{:#04x}", byte)?;
+			if j + 1 < line.len() || trailing_comma || i + 1 < lines {
+				write!(out, ",")?;
+			}
+		}
+		writeln!(out)?;
+	}
+	Ok(())
+}
+
+// Checks the dumped bytes against the expected digest, after the dump has been written.
+#[cfg(feature = "sha256")]
+fn verify(params: &Parameters, path: &Path, data: &[u8], out: &mut dyn Write) -> io::Result<()> {
+	if let Some(ref expected) = params.expect_sha256 {
+		let actual = sha256::digest(data);
+		if actual != *expected {
+			out.flush()?;
+			err_digest(path, expected, &actual);
+		}
+	}
+	Ok(())
+}
+#[cfg(not(feature = "sha256"))]
+fn verify(_params: &Parameters, _path: &Path, _data: &[u8], _out: &mut dyn Write) -> io::Result<()> {
+	Ok(())
+}
+
+//----------------------------------------------------------------
+// SHA-256 for verifying the dumped bytes.
+
+#[cfg(feature = "sha256")]
+mod sha256 {
+	const K: [u32; 64] = [
+		0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+		0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+		0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+		0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+		0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+		0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+		0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+		0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+	];
+
+	pub fn digest(data: &[u8]) -> [u8; 32] {
+		let mut state: [u32; 8] = [
+			0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+		];
+		// Pad with a one bit, zeroes and the length in bits to a multiple of the block size
+		let mut message = data.to_vec();
+		message.push(0x80);
+		while message.len() % 64 != 56 {
+			message.push(0);
+		}
+		message.extend_from_slice(&((data.len() as u64).wrapping_mul(8)).to_be_bytes());
+
+		for block in message.chunks(64) {
+			let mut w = [0u32; 64];
+			for (i, word) in block.chunks(4).enumerate() {
+				w[i] = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+			}
+			for i in 16..64 {
+				let s0 = w[i - 15].rotate_right(7) ^ w[i - 15].rotate_right(18) ^ (w[i - 15] >> 3);
+				let s1 = w[i - 2].rotate_right(17) ^ w[i - 2].rotate_right(19) ^ (w[i - 2] >> 10);
+				w[i] = w[i - 16].wrapping_add(s0).wrapping_add(w[i - 7]).wrapping_add(s1);
+			}
+			let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = state;
+			for i in 0..64 {
+				let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+				let ch = (e & f) ^ (!e & g);
+				let t1 = h.wrapping_add(s1).wrapping_add(ch).wrapping_add(K[i]).wrapping_add(w[i]);
+				let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+				let maj = (a & b) ^ (a & c) ^ (b & c);
+				let t2 = s0.wrapping_add(maj);
+				h = g;
+				g = f;
+				f = e;
+				e = d.wrapping_add(t1);
+				d = c;
+				c = b;
+				b = a;
+				a = t1.wrapping_add(t2);
+			}
+			for (s, v) in state.iter_mut().zip(&[a, b, c, d, e, f, g, h]) {
+				*s = s.wrapping_add(*v);
+			}
+		}
+
+		let mut result = [0u8; 32];
+		for (bytes, word) in result.chunks_mut(4).zip(&state) {
+			bytes.copy_from_slice(&word.to_be_bytes());
+		}
+		result
+	}
+
+	// Parses a digest from 64 hex digits.
+	pub fn parse(text: &str) -> Option<[u8; 32]> {
+		if text.len() != 64 || !text.is_ascii() {
+			return None;
+		}
+		let mut result = [0u8; 32];
+		for (i, byte) in result.iter_mut().enumerate() {
+			*byte = u8::from_str_radix(&text[i * 2..i * 2 + 2], 16).ok()?;
+		}
+		Some(result)
+	}
+
+	pub fn hex(digest: &[u8; 32]) -> String {
+		digest.iter().map(|byte| format!("{:02x}", byte)).collect()
+	}
+}
+
+//----------------------------------------------------------------
+
+// Reconstruct the bytes from a hex dump.
+fn reverse(params: &Parameters, path: &Path, out: &mut dyn Write) -> io::Result<()> {
+	let text = fs::read_to_string(path)
+		.unwrap_or_else(|e| err_file_error(e, path));
+	if let Some(hint) = format_hint(&text) {
+		if DumpFormat::from_name(hint).is_none() {
+			let _ = writeln!(stderr(), "hexdump: {:?}: unknown format {:?}, detecting the format instead.", path, hint);
+		}
+	}
+	let bytes = unhexdump_auto(&text, params.max_bytes)
+		.unwrap_or_else(|e| err_parse(e, path));
+	out.write_all(&bytes)
 }
 
 //----------------------------------------------------------------
 
+fn run(params: &Parameters, out: &mut dyn Write) -> io::Result<()> {
+	if params.reverse {
+		params.paths.iter().try_for_each(|path| reverse(params, path, out))
+	}
+	else if params.diff {
+		diff(params, out)
+	}
+	else if params.concat && !params.paths.is_empty() {
+		dump_concat(params, out)
+	}
+	else {
+		params.paths.iter().enumerate().try_for_each(|(i, path)| {
+			// Blank lines between the dumps, raw bytes and JSON lines are written as is
+			if i > 0 && !params.raw && !params.json_lines {
+				for _ in 0..params.separator {
+					writeln!(out)?;
+				}
+			}
+			dump(params, path, out)
+		})
+	}
+}
+
+// Creates a new temporary file with an unpredictable name, never opening a file or symlink which already exists.
+fn create_temp() -> (PathBuf, fs::File) {
+	let dir = env::temp_dir();
+	let mut options = fs::OpenOptions::new();
+	options.write(true).create_new(true);
+	#[cfg(unix)]
+	::std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+	loop {
+		let suffix = RandomState::new().build_hasher().finish();
+		let path = dir.join(format!("hexdump-{}-{:016x}.txt", process::id(), suffix));
+		match options.open(&path) {
+			Ok(file) => return (path, file),
+			Err(ref e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+			Err(e) => err_file_error(e, &path),
+		}
+	}
+}
+
+// Writes the dump to a temporary file and opens it in the `$EDITOR`, the file is removed afterwards.
+// Without an editor the path of the temporary file is printed instead and the file is kept.
+fn edit(params: &Parameters) {
+	let (path, mut file) = create_temp();
+	*TEMP_FILE.lock().unwrap() = Some(path.clone());
+	run(params, &mut file)
+		.unwrap_or_else(|e| err_file_error(e, &path));
+	drop(file);
+
+	match env::var("EDITOR") {
+		Ok(ref editor) if !editor.trim().is_empty() => {
+			let mut words = editor.split_whitespace();
+			let status = Command::new(words.next().unwrap())
+				.args(words)
+				.arg(&path)
+				.status()
+				.unwrap_or_else(|e| err_editor(e, editor));
+			if !status.success() {
+				exit_with(status.code().unwrap_or(1));
+			}
+			if let Some(path) = TEMP_FILE.lock().unwrap().take() {
+				let _ = fs::remove_file(path);
+			}
+		},
+		_ => {
+			TEMP_FILE.lock().unwrap().take();
+			println!("{}", path.display());
+		},
+	}
+}
+
+// Detects changes to the modification times of the watched files.
+#[derive(Default)]
+struct Watcher {
+	modified: Option<Vec<Option<SystemTime>>>,
+}
+impl Watcher {
+	// Returns whether any of the files changed since the last poll, the first poll always does.
+	fn poll(&mut self, paths: &[PathBuf]) -> bool {
+		let modified: Vec<_> = paths.iter()
+			.map(|path| fs::metadata(path).and_then(|metadata| metadata.modified()).ok())
+			.collect();
+		let changed = self.modified.as_ref() != Some(&modified);
+		self.modified = Some(modified);
+		changed
+	}
+}
+
+// Dumps the files again whenever they change, clears the terminal or separates the dumps by a timestamp.
+fn watch(params: &Parameters) -> ! {
+	let stdout = stdout();
+	let terminal = stdout.is_terminal();
+	let mut watcher = Watcher::default();
+	loop {
+		if watcher.poll(&params.paths) {
+			let mut out = stdout.lock();
+			let result = if terminal { write!(out, "\x1b[2J\x1b[H") } else { writeln!(out, "; ---- {} ----", timestamp(SystemTime::now())) };
+			result.and_then(|_| run(params, &mut out)).and_then(|_| out.flush())
+				.unwrap_or_else(|e| err_output(e));
+		}
+		thread::sleep(Duration::from_millis(500));
+	}
+}
+
+// Formats the time as `YYYY-MM-DD hh:mm:ss UTC`.
+fn timestamp(time: SystemTime) -> String {
+	let secs = time.duration_since(UNIX_EPOCH).map_or(0, |duration| duration.as_secs());
+	// Civil date from the days since the epoch, see http://howardhinnant.github.io/date_algorithms.html
+	let days = secs / 86400 + 719468;
+	let era = days / 146097;
+	let doe = days % 146097;
+	let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+	let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+	let mp = (5 * doy + 2) / 153;
+	let day = doy - (153 * mp + 2) / 5 + 1;
+	let month = if mp < 10 { mp + 3 } else { mp - 9 };
+	let year = yoe + era * 400 + if month <= 2 {1} else {0};
+	format!("{:04}-{:02}-{:02} {:02}:{:02}:{:02} UTC", year, month, day, secs / 3600 % 24, secs / 60 % 60, secs % 60)
+}
+
 fn main() {
 	let params = Parameters::default();
-	for path in &params.paths {
-		dump(&params, &path);
+	// Editing writes to a temporary file, like the output file it never garbles the terminal
+	if params.edit {
+		return edit(&params);
+	}
+	let stdout = stdout();
+	// Reconstructed binary would garble the terminal, watching always writes to it
+	if params.reverse && !params.force && (params.watch || params.output.is_none()) && stdout.is_terminal() {
+		err_terminal();
+	}
+	if params.watch {
+		watch(&params);
+	}
+	if let Some(ref path) = params.output {
+		let mut file = fs::File::create(path)
+			.unwrap_or_else(|e| err_file_error(e, path));
+		return run(&params, &mut file).unwrap_or_else(|e| err_file_error(e, path));
+	}
+	let mut out = stdout.lock();
+	run(&params, &mut out).unwrap_or_else(|e| err_output(e));
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn watcher() {
+		let path = env::temp_dir().join(format!("hexdump-{}-watcher.bin", process::id()));
+		let file = fs::File::create(&path).unwrap();
+		file.set_modified(UNIX_EPOCH + Duration::from_secs(1000)).unwrap();
+		let paths = [path.clone()];
+
+		let mut watcher = Watcher::default();
+		assert!(watcher.poll(&paths));
+		assert!(!watcher.poll(&paths));
+		file.set_modified(UNIX_EPOCH + Duration::from_secs(2000)).unwrap();
+		assert!(watcher.poll(&paths));
+		assert!(!watcher.poll(&paths));
+		// Removing the file is a change as well
+		drop(file);
+		fs::remove_file(&path).unwrap();
+		assert!(watcher.poll(&paths));
+	}
+
+	#[test]
+	fn timestamp() {
+		assert_eq!(super::timestamp(UNIX_EPOCH), "1970-01-01 00:00:00 UTC");
+		assert_eq!(super::timestamp(UNIX_EPOCH + Duration::from_secs(951782400 + 3723)), "2000-02-29 01:02:03 UTC");
 	}
 }
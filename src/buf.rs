@@ -0,0 +1,261 @@
+use ::std::{cmp, error, fmt};
+use super::{braille, byte_columns, hex_column, hex_padding, rel_offset, segment_len, Config, Radix};
+
+/// The buffer is too small for the formatted row.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct BufTooSmall;
+impl fmt::Display for BufTooSmall {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.write_str("buffer too small for the formatted row")
+	}
+}
+impl error::Error for BufTooSmall {}
+
+const HEX_DIGITS: &[u8; 16] = b"0123456789ABCDEF";
+const LOWER_HEX_DIGITS: &[u8; 16] = b"0123456789abcdef";
+
+struct Cursor<'b> {
+	buf: &'b mut [u8],
+	len: usize,
+}
+impl<'b> Cursor<'b> {
+	fn byte(&mut self, byte: u8) -> Result<(), BufTooSmall> {
+		*self.buf.get_mut(self.len).ok_or(BufTooSmall)? = byte;
+		self.len += 1;
+		Ok(())
+	}
+	fn spaces(&mut self, n: usize) -> Result<(), BufTooSmall> {
+		for _ in 0..n {
+			self.byte(b' ')?;
+		}
+		Ok(())
+	}
+	fn str(&mut self, s: &str) -> Result<(), BufTooSmall> {
+		for &byte in s.as_bytes() {
+			self.byte(byte)?;
+		}
+		Ok(())
+	}
+	fn char(&mut self, c: char) -> Result<(), BufTooSmall> {
+		let mut utf8 = [0u8; 4];
+		for &byte in c.encode_utf8(&mut utf8).as_bytes() {
+			self.byte(byte)?;
+		}
+		Ok(())
+	}
+	fn decimal(&mut self, n: usize) -> Result<(), BufTooSmall> {
+		if n >= 10 {
+			self.decimal(n / 10)?;
+		}
+		self.byte(b'0' + (n % 10) as u8)
+	}
+	fn hex(&mut self, value: u64, digits: usize, uppercase: bool) -> Result<(), BufTooSmall> {
+		let table = if uppercase { HEX_DIGITS } else { LOWER_HEX_DIGITS };
+		for i in (0..digits).rev() {
+			self.byte(table[(value >> (i * 4)) as usize & 0xF])?;
+		}
+		Ok(())
+	}
+	fn addr(&mut self, mut value: u64, cfg: &Config) -> Result<(), BufTooSmall> {
+		let base = match cfg.addr_radix {
+			Radix::Hex => 16,
+			Radix::Decimal => 10,
+			Radix::Octal => 8,
+			Radix::Binary => 2,
+		};
+		let table = if cfg.uppercase { HEX_DIGITS } else { LOWER_HEX_DIGITS };
+		// Collect the digits from least significant
+		let mut digits = [0u8; 64];
+		let mut len = 0;
+		while value > 0 || len == 0 {
+			digits[len] = table[(value % base) as usize];
+			value /= base;
+			len += 1;
+		}
+		if cfg.groups_addr() {
+			for i in (0..len).rev() {
+				self.byte(digits[i])?;
+				if i > 0 && i.is_multiple_of(3) {
+					self.byte(b',')?;
+				}
+			}
+			return Ok(());
+		}
+		for _ in len..cfg.addr_min_width {
+			self.byte(b'0')?;
+		}
+		for i in (0..len).rev() {
+			self.byte(digits[i])?;
+		}
+		Ok(())
+	}
+	fn cell(&mut self, byte: u8, radix: Radix, uppercase: bool) -> Result<(), BufTooSmall> {
+		match radix {
+			Radix::Hex => self.hex(byte as u64, 2, uppercase),
+			Radix::Decimal => {
+				let digits = [byte / 100, byte / 10 % 10, byte % 10];
+				let start = if byte >= 100 { 0 } else if byte >= 10 { 1 } else { 2 };
+				self.spaces(start)?;
+				for &digit in &digits[start..] {
+					self.byte(b'0' + digit)?;
+				}
+				Ok(())
+			},
+			Radix::Octal => {
+				for &shift in &[6, 3, 0] {
+					self.byte(b'0' + (byte >> shift & 7))?;
+				}
+				Ok(())
+			},
+			Radix::Binary => {
+				for shift in (0..8).rev() {
+					self.byte(b'0' + (byte >> shift & 1))?;
+				}
+				Ok(())
+			},
+		}
+	}
+}
+
+/// Formats a single row into the buffer without allocating or using `core::fmt`.
+///
+/// The first byte of `row` is at address `addr`, bytes which do not fit in its 16 byte aligned row are ignored.
+/// The row is laid out like the `Display` output without its newline, with the field separators of a schema.
+/// Prefixes, highlights, masks, colors, annotations and run length notes are not written, the nonzero count is.
+/// The columns are in the default order, the gutter and the relative offsets are as wide as for the whole dump.
+/// Returns the number of bytes written.
+///
+/// ```
+/// use hexdump::{format_row_into, hexdump};
+///
+/// let dump = hexdump(b"hexdump", 0);
+/// let mut buf = [0u8; 80];
+/// let len = format_row_into(&mut buf, 0, b"hexdump", dump.config()).unwrap();
+/// assert_eq!(&buf[..len], &b"00000000:  68 65 78 64 75 6D 70                              |hexdump         |"[..]);
+/// ```
+pub fn format_row_into(buf: &mut [u8], addr: u64, row: &[u8], cfg: &Config) -> Result<usize, BufTooSmall> {
+	let skip = (addr % 16) as usize;
+	let row = &row[..cmp::min(row.len(), 16 - skip)];
+	let skep = 16 - skip - row.len();
+	let cell = cfg.cell_width();
+	let mut out = Cursor { buf, len: 0 };
+
+	// Address
+	let display = (addr & cfg.addr_mask) | cfg.addr_base;
+	match cfg.segments.iter().find(|(range, _)| range.contains(&display)) {
+		Some(&(ref range, name)) => {
+			let offset = display - range.start;
+			out.str(name)?;
+			out.str("+0x")?;
+			out.hex(offset, segment_len(name, offset) - name.chars().count() - 3, true)?;
+		},
+		None => out.addr(display, cfg)?,
+	}
+	out.str(cfg.addr_suffix)?;
+	if let Some(base) = cfg.rel_base {
+		let (sign, distance) = rel_offset(addr, base);
+		out.char(sign)?;
+		out.hex(distance, cfg.rel_len(), true)?;
+		out.byte(b' ')?;
+	}
+
+	// Nibble grid without the ASCII gutter, missing bytes are padded
+	if cfg.nibble_grid {
+		out.spaces(cfg.hex_indent + skip * 2)?;
+		for &byte in row {
+			out.hex(byte as u64, 2, cfg.uppercase)?;
+		}
+		out.spaces(skep * 2)?;
+		return notes(out, skip, row, cfg);
+	}
+
+	// Hex bytes
+	let group = cfg.group;
+	let columns = byte_columns(skip, row.len(), group, cfg.little_endian);
+	let columns = &columns[..row.len()];
+	let first = columns.first().map_or(skip, |&(column, _)| column);
+	let span = columns.last().map_or(0, |&(column, _)| column + 1 - first);
+	let (lead, trail) = hex_padding(first, span, cell, group);
+	out.spaces(cfg.hex_indent + lead)?;
+	for (n, &(column, i)) in columns.iter().enumerate() {
+		let byte = row[i];
+		if n > 0 {
+			let gap = hex_column(column, cell, group) - hex_column(columns[n - 1].0, cell, group) - cell;
+			let at = addr.wrapping_add(i as u64).wrapping_sub(cfg.extent.0);
+			if !cfg.little_endian && gap > 0 && cfg.schema.iter().any(|(field, _)| field.start as u64 == at) {
+				out.spaces(gap - 1)?;
+				out.char('·')?;
+			}
+			else {
+				out.spaces(gap)?;
+			}
+		}
+		match cfg.dim_zeros {
+			Some(c) if byte == 0 => for _ in 0..cfg.radix.width() { out.char(c)?; },
+			_ => out.cell(byte, cfg.radix, cfg.uppercase)?,
+		}
+		if cfg.expanded {
+			out.byte(b'(')?;
+			cfg.charset.glyphs(&[byte], |c| out.char(c))?;
+			out.byte(b')')?;
+		}
+	}
+
+	// ASCII gutter, the bytes of an expanded row carry their own glyphs
+	if !cfg.expanded {
+		out.spaces(trail + cfg.gutter_margin)?;
+		if cfg.pipes {
+			out.byte(if cfg.gutter_pipes { b'|' } else { b' ' })?;
+		}
+		let pads = cfg.pad_gutter && cfg.charset.pads();
+		if cfg.swap_gutter && cfg.little_endian {
+			let mut at = 0;
+			for &(column, i) in columns {
+				if pads {
+					out.spaces(column - at)?;
+				}
+				cfg.charset.glyphs(&row[i..i + 1], |c| out.char(c))?;
+				at = column + 1;
+			}
+			if pads {
+				out.spaces(cfg.gutter_width().saturating_sub(at))?;
+			}
+		}
+		else {
+			if pads {
+				out.spaces(skip)?;
+			}
+			cfg.charset.glyphs(row, |c| out.char(c))?;
+			if pads {
+				out.spaces(cfg.gutter_width().saturating_sub(16 - skep))?;
+			}
+		}
+		if cfg.pipes {
+			out.byte(if cfg.gutter_pipes { b'|' } else { b' ' })?;
+		}
+	}
+
+	notes(out, skip, row, cfg)
+}
+
+// Writes the notes after the gutter and trims the row.
+fn notes(mut out: Cursor, skip: usize, row: &[u8], cfg: &Config) -> Result<usize, BufTooSmall> {
+	if cfg.braille_overview {
+		out.byte(b' ')?;
+		out.char(braille(skip, row))?;
+	}
+	if cfg.nonzero_count {
+		for &byte in b" [nz=" {
+			out.byte(byte)?;
+		}
+		out.decimal(row.iter().filter(|&&byte| byte != 0).count())?;
+		out.byte(b']')?;
+	}
+
+	if cfg.no_trailing_ws {
+		while out.len > 0 && out.buf[out.len - 1] == b' ' {
+			out.len -= 1;
+		}
+	}
+	Ok(out.len)
+}
@@ -0,0 +1,188 @@
+use ::std::{fmt, str};
+
+/// How the ASCII gutter interprets the dumped bytes.
+///
+/// Bytes which cannot be displayed are shown as `.`.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum Charset {
+	/// Bytes in the range `0x20..0x80` are shown as is.
+	#[default]
+	Ascii,
+	/// ASCII extended with the printable ISO-8859-1 characters `0xA0..=0xFF`.
+	Latin1,
+	/// Decodes UTF-8 sequences, the glyph is placed over the first byte of the sequence.
+	///
+	/// The remaining bytes of the sequence are shown as spaces.
+	/// Sequences are only decoded within a row, a sequence split by a row boundary is not printable.
+	Utf8,
+	/// EBCDIC code page 037.
+	Ebcdic,
+	/// ASCII with control bytes in caret notation, eg. `^@` for `0x00` and `^?` for `0x7F`.
+	///
+	/// Control bytes take up two columns, rows containing them are wider than the gutter.
+	Caret,
+	/// Decodes UTF-16 little endian code units, the glyph is placed over the first byte of each pair.
+	///
+	/// Pairs start at the first byte of every row, a pair split by a row boundary, an odd trailing byte
+	/// and surrogates are not printable.
+	Utf16Le,
+	/// Decodes UTF-16 big endian code units, like `Utf16Le`.
+	Utf16Be,
+	/// Printable ASCII as is, other bytes in hex like `<00>` so no information is lost.
+	///
+	/// The `<` is shown as `<3C>` to keep it unambiguous. The glyphs have different widths,
+	/// the gutter is as wide as its glyphs and is not padded for the missing bytes of a row.
+	MixedHex,
+}
+
+impl Charset {
+	// Number of columns the glyph for the byte takes up.
+	pub(crate) fn width(self, byte: u8) -> usize {
+		match self {
+			Charset::Caret if byte < 0x20 || byte == 0x7F => 2,
+			Charset::MixedHex if !(0x20..0x7F).contains(&byte) || byte == b'<' => 4,
+			_ => 1,
+		}
+	}
+	// Whether the gutter is padded with a space for every missing byte of a row.
+	pub(crate) fn pads(self) -> bool {
+		self != Charset::MixedHex
+	}
+	pub(crate) fn write(self, f: &mut dyn fmt::Write, bytes: &[u8]) -> fmt::Result {
+		self.glyphs(bytes, |c| f.write_char(c))
+	}
+	// Emits the characters of the glyphs for the bytes.
+	pub(crate) fn glyphs<E, F: FnMut(char) -> Result<(), E>>(self, bytes: &[u8], mut emit: F) -> Result<(), E> {
+		match self {
+			Charset::Ascii => {
+				for &byte in bytes {
+					emit(ascii(byte))?;
+				}
+			},
+			Charset::Latin1 => {
+				for &byte in bytes {
+					emit(if byte >= 0xA0 { byte as char } else { ascii(byte) })?;
+				}
+			},
+			Charset::Utf8 => {
+				let mut i = 0;
+				while i < bytes.len() {
+					let len = match bytes[i] {
+						0xC2..=0xDF => 2,
+						0xE0..=0xEF => 3,
+						0xF0..=0xF4 => 4,
+						_ => 1,
+					};
+					let decoded = if len > 1 { bytes.get(i..i + len).and_then(|seq| str::from_utf8(seq).ok()) } else { None };
+					match decoded.and_then(|s| s.chars().next()) {
+						Some(c) => {
+							emit(if c.is_control() { '.' } else { c })?;
+							for _ in 1..len {
+								emit(' ')?;
+							}
+							i += len;
+						},
+						None => {
+							emit(ascii(bytes[i]))?;
+							i += 1;
+						},
+					}
+				}
+			},
+			Charset::Ebcdic => {
+				for &byte in bytes {
+					emit(if byte >= 0x40 { EBCDIC[byte as usize - 0x40] } else { '.' })?;
+				}
+			},
+			Charset::Utf16Le | Charset::Utf16Be => {
+				for pair in bytes.chunks(2) {
+					let unit = match *pair {
+						[lo, hi] if self == Charset::Utf16Le => Some(u16::from_le_bytes([lo, hi])),
+						[hi, lo] => Some(u16::from_be_bytes([hi, lo])),
+						_ => None,
+					};
+					// Surrogates are not decoded as they need the next pair
+					match unit.and_then(|unit| char::from_u32(unit as u32)) {
+						Some(c) => emit(if c.is_control() { '.' } else { c })?,
+						None => emit('.')?,
+					}
+					if pair.len() == 2 {
+						emit(' ')?;
+					}
+				}
+			},
+			Charset::MixedHex => {
+				for &byte in bytes {
+					if self.width(byte) == 1 {
+						emit(byte as char)?;
+					}
+					else {
+						emit('<')?;
+						emit(HEX_DIGITS[(byte >> 4) as usize] as char)?;
+						emit(HEX_DIGITS[(byte & 0xF) as usize] as char)?;
+						emit('>')?;
+					}
+				}
+			},
+			Charset::Caret => {
+				for &byte in bytes {
+					match byte {
+						0x00..=0x1F => {
+							emit('^')?;
+							emit((byte + 0x40) as char)?;
+						},
+						0x7F => {
+							emit('^')?;
+							emit('?')?;
+						},
+						_ => emit(ascii(byte))?,
+					}
+				}
+			},
+		}
+		Ok(())
+	}
+}
+
+const HEX_DIGITS: &[u8; 16] = b"0123456789ABCDEF";
+
+fn ascii(byte: u8) -> char {
+	if !is_printable(byte) { '.' }
+	else { byte as char }
+}
+fn is_printable(byte: u8) -> bool {
+	(0x20..0x80).contains(&byte)
+}
+
+/// Returns the fraction of the bytes the ASCII charset shows as is, an empty buffer has a ratio of `0.0`.
+///
+/// Useful as a heuristic to tell text from binary data.
+///
+/// ```
+/// use hexdump::printable_ratio;
+///
+/// assert_eq!(printable_ratio(b"hex\0"), 0.75);
+/// ```
+pub fn printable_ratio(bytes: &[u8]) -> f32 {
+	if bytes.is_empty() {
+		return 0.0;
+	}
+	let printable = bytes.iter().filter(|&&byte| is_printable(byte)).count();
+	printable as f32 / bytes.len() as f32
+}
+
+// Code page 037, starting at 0x40 as everything before is a control byte.
+static EBCDIC: [char; 0xC0] = [
+	' ', ' ', 'â', 'ä', 'à', 'á', 'ã', 'å', 'ç', 'ñ', '¢', '.', '<', '(', '+', '|',
+	'&', 'é', 'ê', 'ë', 'è', 'í', 'î', 'ï', 'ì', 'ß', '!', '$', '*', ')', ';', '¬',
+	'-', '/', 'Â', 'Ä', 'À', 'Á', 'Ã', 'Å', 'Ç', 'Ñ', '¦', ',', '%', '_', '>', '?',
+	'ø', 'É', 'Ê', 'Ë', 'È', 'Í', 'Î', 'Ï', 'Ì', '`', ':', '#', '@', '\'', '=', '"',
+	'Ø', 'a', 'b', 'c', 'd', 'e', 'f', 'g', 'h', 'i', '«', '»', 'ð', 'ý', 'þ', '±',
+	'°', 'j', 'k', 'l', 'm', 'n', 'o', 'p', 'q', 'r', 'ª', 'º', 'æ', '¸', 'Æ', '¤',
+	'µ', '~', 's', 't', 'u', 'v', 'w', 'x', 'y', 'z', '¡', '¿', 'Ð', 'Ý', 'Þ', '®',
+	'^', '£', '¥', '·', '©', '§', '¶', '¼', '½', '¾', '[', ']', '¯', '¨', '´', '×',
+	'{', 'A', 'B', 'C', 'D', 'E', 'F', 'G', 'H', 'I', '.', 'ô', 'ö', 'ò', 'ó', 'õ',
+	'}', 'J', 'K', 'L', 'M', 'N', 'O', 'P', 'Q', 'R', '¹', 'û', 'ü', 'ù', 'ú', 'ÿ',
+	'\\', '÷', 'S', 'T', 'U', 'V', 'W', 'X', 'Y', 'Z', '²', 'Ô', 'Ö', 'Ò', 'Ó', 'Õ',
+	'0', '1', '2', '3', '4', '5', '6', '7', '8', '9', '³', 'Û', 'Ü', 'Ù', 'Ú', '.',
+];
@@ -0,0 +1,138 @@
+use ::std::fmt;
+use super::BuildError;
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+enum Kind {
+	Unsigned,
+	Signed,
+	Float,
+}
+
+// A primitive field of a record, eg. `u32le`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+struct Field {
+	kind: Kind,
+	size: usize,
+	little_endian: bool,
+}
+impl Field {
+	fn parse(name: &str) -> Option<Field> {
+		let kind = match name.as_bytes().first() {
+			Some(b'u') => Kind::Unsigned,
+			Some(b'i') => Kind::Signed,
+			Some(b'f') => Kind::Float,
+			_ => return None,
+		};
+		let rest = &name[1..];
+		let (bits, order) = match (rest.strip_suffix("le"), rest.strip_suffix("be")) {
+			(Some(bits), _) => (bits, Some(true)),
+			(_, Some(bits)) => (bits, Some(false)),
+			_ => (rest, None),
+		};
+		// Bytes have no byte order, wider fields need it spelled out
+		let size = match (kind, bits, order) {
+			(Kind::Float, "8", _) | (Kind::Float, "16", _) => return None,
+			(_, "8", None) => 1,
+			(_, "16", Some(_)) => 2,
+			(_, "32", Some(_)) => 4,
+			(_, "64", Some(_)) => 8,
+			_ => return None,
+		};
+		let little_endian = order.unwrap_or(true);
+		Some(Field { kind, size, little_endian })
+	}
+	fn write(&self, f: &mut fmt::Formatter, bytes: &[u8]) -> fmt::Result {
+		let mut raw = [0u8; 8];
+		raw[..self.size].copy_from_slice(bytes);
+		if !self.little_endian {
+			raw[..self.size].reverse();
+		}
+		let value = u64::from_le_bytes(raw);
+		let bits = self.size * 8;
+		match self.kind {
+			Kind::Unsigned => write!(f, "{}", value),
+			// Sign extend from the width of the field
+			Kind::Signed => write!(f, "{}", (value << (64 - bits)) as i64 >> (64 - bits)),
+			Kind::Float if self.size == 4 => write!(f, "{}", f32::from_bits(value as u32)),
+			Kind::Float => write!(f, "{}", f64::from_bits(value)),
+		}
+	}
+}
+impl fmt::Display for Field {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		let kind = match self.kind {
+			Kind::Unsigned => 'u',
+			Kind::Signed => 'i',
+			Kind::Float => 'f',
+		};
+		let order = match self.size {
+			1 => "",
+			_ if self.little_endian => "le",
+			_ => "be",
+		};
+		write!(f, "{}{}{}", kind, self.size * 8, order)
+	}
+}
+
+/// Layout of a fixed size record as a list of primitive fields.
+///
+/// Parsed from comma separated field types: `u8`, `i8` and `u`, `i` with `16`, `32` or `64` bits and `f` with `32` or
+/// `64` bits, followed by the byte order `le` or `be`, eg. `u32le,u16le,f32be`.
+///
+/// ```
+/// use hexdump::ChunkLayout;
+///
+/// let layout = ChunkLayout::parse("u32le,u16be,i8").unwrap();
+/// assert_eq!(layout.size(), 7);
+/// assert_eq!(layout.decode(b"\x01\x00\x00\x00\x00\x02\xFF").unwrap().to_string(), "u32le=1 u16be=2 i8=-1");
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ChunkLayout {
+	fields: Vec<Field>,
+}
+impl ChunkLayout {
+	/// Parses the field types of the layout.
+	pub fn parse(layout: &str) -> Result<ChunkLayout, BuildError> {
+		if layout.trim().is_empty() {
+			return Err(BuildError::EmptyLayout);
+		}
+		let fields = layout.split(',').enumerate()
+			.map(|(field, name)| Field::parse(name.trim()).ok_or(BuildError::InvalidField { field }))
+			.collect::<Result<Vec<Field>, _>>()?;
+		Ok(ChunkLayout { fields })
+	}
+	/// Returns the size of a record in bytes.
+	pub fn size(&self) -> usize {
+		self.fields.iter().map(|field| field.size).sum()
+	}
+	/// Decodes a record, returns `None` unless the record is exactly as large as the layout.
+	///
+	/// The decoded record displays as its fields labeled with their types, eg. `u32le=1 u16le=2`.
+	pub fn decode<'b>(&'b self, record: &'b [u8]) -> Option<Record<'b>> {
+		if record.len() != self.size() {
+			return None;
+		}
+		Some(Record { layout: self, bytes: record })
+	}
+}
+
+/// A record decoded by its layout, see `ChunkLayout::decode`.
+#[derive(Copy, Clone, Debug)]
+pub struct Record<'b> {
+	layout: &'b ChunkLayout,
+	bytes: &'b [u8],
+}
+impl<'b> fmt::Display for Record<'b> {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		let mut at = 0;
+		for (i, field) in self.layout.fields.iter().enumerate() {
+			if i > 0 {
+				f.write_str(" ")?;
+			}
+			write!(f, "{}=", field)?;
+			field.write(f, &self.bytes[at..at + field.size])?;
+			at += field.size;
+		}
+		Ok(())
+	}
+}
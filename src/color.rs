@@ -0,0 +1,52 @@
+/// Foreground colors for the classes of bytes in the hex column, as 24-bit RGB.
+///
+/// Classes without a color are not colored. The default theme suits dark terminal backgrounds.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct ColorTheme {
+	/// Color for `0x00`.
+	pub zero: Option<(u8, u8, u8)>,
+	/// Color for the control bytes `0x01..0x20` and `0x7F`.
+	pub control: Option<(u8, u8, u8)>,
+	/// Color for the printable bytes `0x20..0x7F`.
+	pub printable: Option<(u8, u8, u8)>,
+	/// Color for the bytes `0x80..=0xFF`.
+	pub high: Option<(u8, u8, u8)>,
+}
+
+impl Default for ColorTheme {
+	fn default() -> ColorTheme {
+		ColorTheme {
+			zero: Some((128, 128, 128)),
+			control: Some((205, 49, 49)),
+			printable: Some((13, 188, 121)),
+			high: Some((229, 229, 16)),
+		}
+	}
+}
+
+impl ColorTheme {
+	/// Theme using the Solarized accent colors, suits both light and dark backgrounds.
+	pub const SOLARIZED: ColorTheme = ColorTheme {
+		zero: Some((88, 110, 117)),
+		control: Some((220, 50, 47)),
+		printable: Some((38, 139, 210)),
+		high: Some((181, 137, 0)),
+	};
+	/// Theme without any colors.
+	pub const MONO: ColorTheme = ColorTheme {
+		zero: None,
+		control: None,
+		printable: None,
+		high: None,
+	};
+
+	/// Returns the color for the byte.
+	pub fn color(&self, byte: u8) -> Option<(u8, u8, u8)> {
+		match byte {
+			0x00 => self.zero,
+			0x01..=0x1F | 0x7F => self.control,
+			0x20..=0x7E => self.printable,
+			_ => self.high,
+		}
+	}
+}
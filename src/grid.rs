@@ -0,0 +1,59 @@
+use ::std::cmp;
+use ::std::fmt::{self, Write};
+
+/// Side by side hex dump of several slices, see `hexgrid`.
+#[derive(Copy, Clone, Debug)]
+pub struct HexGrid<'a> {
+	slices: &'a [&'a [u8]],
+	offset: u64,
+}
+
+/// Dumps several slices side by side for comparison.
+///
+/// Every row shows the address once followed by the hex bytes of each slice for that row.
+/// Slices shorter than the longest are padded with blanks.
+///
+/// ```
+/// use hexdump::hexgrid;
+///
+/// assert_eq!(format!("{}", hexgrid(&[b"ab", b"ac"], 0)),
+/// 	"00000000:  61 62                                             61 63\n");
+/// ```
+pub fn hexgrid<'a>(slices: &'a [&'a [u8]], offset: usize) -> HexGrid<'a> {
+	HexGrid { slices, offset: offset as u64 }
+}
+
+impl<'a> fmt::Display for HexGrid<'a> {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		let len = self.slices.iter().map(|slice| slice.len()).max().unwrap_or(0);
+		let mut line = String::new();
+		let mut index = 0;
+		while index < len {
+			let address = self.offset.wrapping_add(index as u64);
+			let skip = (address % 16) as usize;
+			let end = cmp::min(index + 16 - skip, len);
+
+			line.clear();
+			write!(line, "{:08X}: ", address)?;
+			for slice in self.slices {
+				line.push(' ');
+				for n in 0..16 {
+					// Double space every 8 bytes
+					if n > 0 {
+						line.push_str(if n == 8 { "  " } else { " " });
+					}
+					let i = (index + n).wrapping_sub(skip);
+					match slice.get(i) {
+						Some(byte) if n >= skip && i < end => write!(line, "{:02X}", byte)?,
+						_ => line.push_str("  "),
+					}
+				}
+				line.push(' ');
+			}
+			// The padding of the last slice is not needed
+			writeln!(f, "{}", line.trim_end())?;
+			index = end;
+		}
+		Ok(())
+	}
+}
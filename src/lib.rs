@@ -33,94 +33,1720 @@ assert_eq!(format!("{}", datadump(&42)),
 ```
 */
 
+#![allow(clippy::tabs_in_doc_comments)]
+
+mod charset;
+pub use charset::{printable_ratio, Charset};
+
+mod color;
+pub use color::ColorTheme;
+
+mod preset;
+pub use preset::Preset;
+
+mod unhex;
+pub use unhex::{format_hint, unhexdump, unhexdump_auto, unhexdump_stream, DumpFormat, ParseError};
+
+mod lines;
+pub use lines::IntoLines;
+
+mod grid;
+pub use grid::{hexgrid, HexGrid};
+
+mod buf;
+pub use buf::{format_row_into, BufTooSmall};
+
+mod pattern;
+pub use pattern::BuildError;
+
+mod schema;
+
+mod chunk;
+pub use chunk::{ChunkLayout, Record};
+
+mod ring;
+pub use ring::{hexdump_ring, RingDump};
+
+/// Formatting options of a hex dump.
+///
+/// The options are set through the builder methods of `HexDump`.
+#[derive(Debug, Clone)]
+pub struct Config<'a> {
+	annotations: &'a [(Range<usize>, &'a str)],
+	labels: &'a [(u64, &'a str)],
+	schema: Vec<(Range<usize>, &'a str)>,
+	// Offset and length of the dumped bytes, to format single rows like the dump
+	extent: (u64, usize),
+	framed: bool,
+	compact_gutter: bool,
+	charset: Charset,
+	highlights: &'a [Range<usize>],
+	patterns: Vec<pattern::Pattern>,
+	rle_note: usize,
+	nonzero_count: bool,
+	braille_overview: bool,
+	nibble_grid: bool,
+	columns: Vec<Column>,
+	no_trailing_ws: bool,
+	histogram: usize,
+	addr_base: u64,
+	addr_mask: u64,
+	addr_min_width: usize,
+	addr_radix: Radix,
+	addr_grouping: bool,
+	squeeze: bool,
+	gutter_margin: usize,
+	dim_zeros: Option<char>,
+	mask: Option<(&'a [u8], char)>,
+	colors: Option<ColorTheme>,
+	entropy_shading: bool,
+	against_fill: Option<u8>,
+	filter_contains: Option<u8>,
+	radix: Radix,
+	record_size: usize,
+	rel_base: Option<u64>,
+	segments: &'a [(Range<u64>, &'a str)],
+	prefix_fn: Option<PrefixFn<'a>>,
+	line_numbers: bool,
+	addr_links: Option<UrlFn<'a>>,
+	uppercase: bool,
+	group: usize,
+	little_endian: bool,
+	swap_gutter: bool,
+	addr_suffix: &'a str,
+	hex_indent: usize,
+	pipes: bool,
+	gutter_pipes: bool,
+	pad_gutter: bool,
+	final_addr: bool,
+	expanded: bool,
+}
+impl<'a> Default for Config<'a> {
+	fn default() -> Config<'a> {
+		Config {
+			annotations: &[],
+			labels: &[],
+			schema: Vec::new(),
+			extent: (0, 0),
+			framed: false,
+			compact_gutter: false,
+			charset: Charset::Ascii,
+			highlights: &[],
+			patterns: Vec::new(),
+			rle_note: 0,
+			nonzero_count: false,
+			braille_overview: false,
+			nibble_grid: false,
+			columns: Column::DEFAULT.to_vec(),
+			no_trailing_ws: false,
+			histogram: 0,
+			addr_base: 0,
+			addr_mask: !0,
+			addr_min_width: 8,
+			addr_radix: Radix::Hex,
+			addr_grouping: false,
+			squeeze: false,
+			gutter_margin: 2,
+			dim_zeros: None,
+			mask: None,
+			colors: None,
+			entropy_shading: false,
+			against_fill: None,
+			filter_contains: None,
+			radix: Radix::Hex,
+			record_size: 0,
+			rel_base: None,
+			segments: &[],
+			prefix_fn: None,
+			line_numbers: false,
+			addr_links: None,
+			uppercase: true,
+			group: 1,
+			little_endian: false,
+			swap_gutter: false,
+			addr_suffix: ": ",
+			hex_indent: 1,
+			pipes: true,
+			gutter_pipes: true,
+			pad_gutter: true,
+			final_addr: false,
+			expanded: false,
+		}
+	}
+}
+impl<'a> Config<'a> {
+	// Columns of the ASCII gutter, only as wide as the widest row of a compact gutter.
+	fn gutter_width(&self) -> usize {
+		if self.compact_gutter { cmp::min((self.extent.0 % 16) as usize + self.extent.1, 16) } else { 16 }
+	}
+	// Digits of the relative offsets, wide enough for every byte of the dump.
+	fn rel_len(&self) -> usize {
+		let (offset, len) = self.extent;
+		let last = offset.wrapping_add(cmp::max(len, 1) as u64 - 1);
+		rel_len(offset, last, self.rel_base.unwrap_or(0))
+	}
+	// Number of characters of a byte in the hex column.
+	fn cell_width(&self) -> usize {
+		self.radix.width() + if self.expanded {3} else {0}
+	}
+	// Whether the digits of the addresses are grouped by thousands.
+	fn groups_addr(&self) -> bool {
+		self.addr_grouping && self.addr_radix == Radix::Decimal
+	}
+	/// Mimics the default output of `xxd`.
+	///
+	/// Lowercase hex in groups of two bytes and the ASCII gutter without pipes or padding:
+	///
+	/// ```text
+	/// 00000000: 4865 6c6c 6f0a                           Hello.
+	/// ```
+	///
+	/// The ASCII charset shows `0x7F` as is where `xxd` shows a `.`.
+	pub fn xxd_default() -> Config<'a> {
+		Config {
+			uppercase: false,
+			group: 2,
+			hex_indent: 0,
+			pipes: false,
+			pad_gutter: false,
+			..Config::default()
+		}
+	}
+	/// Mimics the output of `hexdump -C`.
+	///
+	/// Lowercase hex without a colon after the address, the ASCII gutter is not padded,
+	/// repeated rows are squeezed, also at the end, and the dump ends with the address after the last byte:
+	///
+	/// ```text
+	/// 00000000  48 65 6c 6c 6f 0a                                 |Hello.|
+	/// 00000006
+	/// ```
+	pub fn bsd_default() -> Config<'a> {
+		Config {
+			uppercase: false,
+			addr_suffix: "  ",
+			hex_indent: 0,
+			pad_gutter: false,
+			squeeze: true,
+			final_addr: true,
+			..Config::default()
+		}
+	}
+}
+
+// Closure writing the prefix of a row, shared between the clones of a config.
+type WritePrefix<'a> = dyn Fn(&mut fmt::Formatter, usize, u64) -> fmt::Result + 'a;
+#[derive(Clone)]
+struct PrefixFn<'a>(Rc<WritePrefix<'a>>);
+impl<'a> fmt::Debug for PrefixFn<'a> {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.write_str("PrefixFn")
+	}
+}
+
+// Closure returning the link target of an address.
+type AddrUrl<'a> = dyn Fn(u64) -> String + 'a;
+#[derive(Clone)]
+struct UrlFn<'a>(Rc<AddrUrl<'a>>);
+impl<'a> fmt::Debug for UrlFn<'a> {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.write_str("UrlFn")
+	}
+}
+
 #[derive(Debug, Clone)]
 pub struct HexDump<'a> {
 	bytes: &'a [u8],
-	offset: usize,
+	offset: u64,
+	cfg: Config<'a>,
+}
+impl<'a> HexDump<'a> {
+	#[inline]
+	fn new(bytes: &'a [u8], offset: u64) -> HexDump<'a> {
+		HexDump {
+			bytes,
+			offset,
+			cfg: Config { extent: (offset, bytes.len()), ..Config::default() },
+		}
+	}
+	/// Returns the formatting options.
+	#[inline]
+	pub fn config(&self) -> &Config<'a> {
+		&self.cfg
+	}
+	/// Sets the address of the first byte, the full 64-bit range is supported.
+	#[inline]
+	pub fn display_offset(mut self, offset: u64) -> HexDump<'a> {
+		self.offset = offset;
+		self.cfg.extent.0 = offset;
+		self
+	}
+	/// Emphasizes the bytes in the given ranges, relative to the start of the dumped bytes.
+	///
+	/// The ranges are borrowed so they can be kept around and reused between dumps.
+	/// Overlapping ranges are merged, the emphasis uses the ANSI reverse video escape codes.
+	#[inline]
+	pub fn highlights(mut self, ranges: &'a [Range<usize>]) -> HexDump<'a> {
+		self.cfg.highlights = ranges;
+		self
+	}
+	/// Emphasizes every match of a pattern of space separated hex bytes, `??` matches any byte.
+	///
+	/// ```
+	/// use hexdump::hexdump;
+	///
+	/// let dump = hexdump(b"\x48\x83\xEC\x28\xE8", 0).find_pattern("48 ?? EC").unwrap();
+	/// assert_eq!(dump.effective_highlights(), [0..3]);
+	/// assert!(hexdump(b"", 0).find_pattern("48 8").is_err());
+	/// ```
+	pub fn find_pattern(mut self, pattern: &str) -> Result<HexDump<'a>, BuildError> {
+		self.cfg.patterns.push(pattern::parse(pattern)?);
+		Ok(self)
+	}
+	/// Notes runs of at least `min_run` identical bytes within a row after the ASCII gutter.
+	///
+	/// Each run is noted as `(00 x4 @0x10)`, a `min_run` of zero disables the notes.
+	#[inline]
+	pub fn rle_note(mut self, min_run: usize) -> HexDump<'a> {
+		self.cfg.rle_note = min_run;
+		self
+	}
+	/// Notes the number of nonzero bytes of each row after the ASCII gutter, eg. `[nz=7]`.
+	#[inline]
+	pub fn nonzero_count(mut self, yes: bool) -> HexDump<'a> {
+		self.cfg.nonzero_count = yes;
+		self
+	}
+	/// Appends a braille character after the ASCII gutter with a dot for every pair of columns holding a nonzero byte.
+	///
+	/// The left column of dots covers the first 8 bytes of the row from top to bottom, the right column the last 8 bytes.
+	/// An all-zero row shows the blank `⠀` and a full row of nonzero bytes shows `⣿`.
+	#[inline]
+	pub fn braille_overview(mut self, yes: bool) -> HexDump<'a> {
+		self.cfg.braille_overview = yes;
+		self
+	}
+	/// Writes the bytes of a row as one run of 32 hex digits without separators and without the ASCII gutter.
+	///
+	/// The digits are always hex, the radix and the column order are ignored.
+	///
+	/// Repeating bit patterns, like in bitmaps and tiles, line up vertically in the columns of the nibbles.
+	#[inline]
+	pub fn nibble_grid(mut self, yes: bool) -> HexDump<'a> {
+		self.cfg.nibble_grid = yes;
+		self
+	}
+	/// Writes the columns of every row in the given order, columns not in the order are left out.
+	///
+	/// The prefixes stay in front of the row. The annotations are only written when the hex bytes come first
+	/// or right after the address. Fails without a `Hex` or `Ascii` column.
+	/// A nibble grid has no gutter to move around and ignores the order.
+	///
+	/// ```
+	/// use hexdump::{hexdump, Column};
+	///
+	/// let dump = hexdump(b"hexdump", 0).columns(&[Column::Ascii, Column::Address]).unwrap();
+	/// assert_eq!(format!("{}", dump), "|hexdump         |00000000: \n");
+	/// ```
+	pub fn columns(mut self, order: &[Column]) -> Result<HexDump<'a>, BuildError> {
+		if !order.iter().any(|&column| column == Column::Hex || column == Column::Ascii) {
+			return Err(BuildError::NoContentColumn);
+		}
+		self.cfg.columns = order.to_vec();
+		Ok(self)
+	}
+	/// Trims the spaces at the end of every line, like the padding of the last row without pipes.
+	///
+	/// The columns keep their alignment, only the spaces after the last character of a line are removed.
+	/// Useful for dumps committed as fixtures where trailing whitespace is flagged.
+	#[inline]
+	pub fn no_trailing_ws(mut self, yes: bool) -> HexDump<'a> {
+		self.cfg.no_trailing_ws = yes;
+		self
+	}
+	/// Notes the `top_n` most frequent byte values and their counts after the dump, eg. `; histogram: 00 x52, FF x3`.
+	///
+	/// Values with the same count are listed in ascending order.
+	#[inline]
+	pub fn histogram(mut self, top_n: usize) -> HexDump<'a> {
+		self.cfg.histogram = top_n;
+		self
+	}
+	/// Selects how the ASCII gutter interprets the bytes.
+	#[inline]
+	pub fn charset(mut self, charset: Charset) -> HexDump<'a> {
+		self.cfg.charset = charset;
+		self
+	}
+	/// Maps the addresses through a base and mask before they are displayed.
+	///
+	/// The displayed address is `(address & mask) | base`, eg. the mask keeps the offset within a page
+	/// while the base supplies the bits of where the page is mapped. Rows stay aligned to the original addresses.
+	#[inline]
+	pub fn addr_map(mut self, base: u64, mask: u64) -> HexDump<'a> {
+		self.cfg.addr_base = base;
+		self.cfg.addr_mask = mask;
+		self
+	}
+	/// Sets the minimum number of digits of the addresses, the default is 8.
+	///
+	/// Addresses which do not fit are shown with as many digits as they need.
+	#[inline]
+	pub fn addr_min_width(mut self, digits: usize) -> HexDump<'a> {
+		self.cfg.addr_min_width = cmp::max(digits, 1);
+		self
+	}
+	/// Selects the radix of the addresses, the default is hex.
+	///
+	/// The addresses are zero padded to the minimum width in any radix.
+	#[inline]
+	pub fn addr_radix(mut self, radix: Radix) -> HexDump<'a> {
+		self.cfg.addr_radix = radix;
+		self
+	}
+	/// Groups the digits of decimal addresses by thousands with commas, eg. `1,048,576:`.
+	///
+	/// Grouped addresses are not zero padded, instead every row is padded to the widest address
+	/// so the hex bytes stay aligned. Has no effect on the other radixes.
+	#[inline]
+	pub fn addr_grouping(mut self, yes: bool) -> HexDump<'a> {
+		self.cfg.addr_grouping = yes;
+		self
+	}
+	/// Replaces all the formatting options, eg. with `Config::xxd_default()`.
+	#[inline]
+	pub fn with_config(mut self, cfg: Config<'a>) -> HexDump<'a> {
+		self.cfg = Config { extent: (self.offset, self.bytes.len()), ..cfg };
+		self
+	}
+	/// Prints the hex digits of the addresses and bytes in uppercase, the default, or lowercase.
+	#[inline]
+	pub fn uppercase(mut self, yes: bool) -> HexDump<'a> {
+		self.cfg.uppercase = yes;
+		self
+	}
+	/// Groups the hex bytes without spaces in between, with a space between the groups.
+	///
+	/// The default of a single byte per group has an extra space in the middle of the row.
+	#[inline]
+	pub fn group(mut self, bytes: usize) -> HexDump<'a> {
+		self.cfg.group = cmp::max(bytes, 1);
+		self
+	}
+	/// Shows the groups as little endian words like `xxd -e`, the bytes within each group are reversed.
+	///
+	/// The bytes of a partial group at the start or end of a row are shown where the word would place them.
+	#[inline]
+	pub fn little_endian(mut self, yes: bool) -> HexDump<'a> {
+		self.cfg.little_endian = yes;
+		self
+	}
+	/// With `little_endian`, reverses the bytes within the groups of the ASCII gutter as well so each word reads like its hex.
+	///
+	/// The gutter then no longer shows the bytes in memory order. The glyphs are decoded one byte at a time.
+	#[inline]
+	pub fn swap_gutter(mut self, yes: bool) -> HexDump<'a> {
+		self.cfg.swap_gutter = yes;
+		self
+	}
+	/// Shows every byte as its hex and glyph, eg. `48(H)`, instead of the ASCII gutter.
+	///
+	/// The glyphs come from the charset. Meant for teaching, each row still holds 16 bytes.
+	#[inline]
+	pub fn expanded(mut self, yes: bool) -> HexDump<'a> {
+		self.cfg.expanded = yes;
+		self
+	}
+	/// Writes a prefix before the address of every row.
+	///
+	/// The closure is called with the index of the row, counting the rows of bytes, and the address of its first byte.
+	/// Annotations are shifted to stay aligned with the bytes.
+	#[inline]
+	pub fn prefix_fn<F: Fn(&mut fmt::Formatter, usize, u64) -> fmt::Result + 'a>(mut self, f: F) -> HexDump<'a> {
+		self.cfg.prefix_fn = Some(PrefixFn(Rc::new(f)));
+		self
+	}
+	/// Numbers the rows of bytes from 1, right-justified in 6 columns before the prefix and the address.
+	///
+	/// The lines which are not rows, like squeezed rows and annotations, are not counted.
+	#[inline]
+	pub fn line_numbers(mut self, yes: bool) -> HexDump<'a> {
+		self.cfg.line_numbers = yes;
+		self
+	}
+	/// Wraps the address of every row in an OSC 8 hyperlink to the target returned by `url_fn`.
+	///
+	/// The closure is called with the displayed address. Terminals without support for hyperlinks ignore them.
+	///
+	/// ```
+	/// use hexdump::hexdump;
+	///
+	/// let dump = format!("{}", hexdump(b"hexdump", 0x10).addr_links(|address| format!("file:///tmp/dump#{:X}", address)));
+	/// assert!(dump.starts_with("\x1b]8;;file:///tmp/dump#10\x1b\\00000010\x1b]8;;\x1b\\:  "));
+	/// ```
+	#[inline]
+	pub fn addr_links<F: Fn(u64) -> String + 'a>(mut self, url_fn: F) -> HexDump<'a> {
+		self.cfg.addr_links = Some(UrlFn(Rc::new(url_fn)));
+		self
+	}
+	/// Shows the addresses relative to the segment containing them, eg. `.text+0x10`.
+	///
+	/// The segments are given in displayed addresses, a row is labeled by the segment containing its first byte.
+	/// Rows outside any segment show their absolute address, the addresses are padded to the widest label.
+	///
+	/// The labels do not say where the segments start, `unhexdump` rejects their rows with `InvalidRow`.
+	#[inline]
+	pub fn segments(mut self, segs: &'a [(Range<u64>, &'a str)]) -> HexDump<'a> {
+		self.cfg.segments = segs;
+		self
+	}
+	/// Only shows the rows with at least one byte different from the fill byte.
+	///
+	/// Consecutive rows filled with the byte are collapsed into a single `; ---- N bytes of XX ----` line.
+	#[inline]
+	pub fn against_fill(mut self, byte: u8) -> HexDump<'a> {
+		self.cfg.against_fill = Some(byte);
+		self
+	}
+	/// Only shows the rows containing the byte, eg. `0xCC` to find the breakpoints.
+	///
+	/// Consecutive rows without the byte are collapsed into a single `; ---- N bytes skipped ----` line.
+	/// Combine with `find_pattern` to also highlight the byte.
+	#[inline]
+	pub fn filter_contains(mut self, byte: u8) -> HexDump<'a> {
+		self.cfg.filter_contains = Some(byte);
+		self
+	}
+	/// Splits the dump in records of a fixed number of bytes, relative to the start of the dumped bytes.
+	///
+	/// A `--- record N ---` line separates the records, counting from record 0.
+	/// A record ending in the middle of a row closes the row early, the next record starts on a new row
+	/// with its bytes in the same columns as without records. A size of zero disables records.
+	#[inline]
+	pub fn record_size(mut self, bytes: usize) -> HexDump<'a> {
+		self.cfg.record_size = bytes;
+		self
+	}
+	/// Replaces full rows identical to the row before them with a single `*` line.
+	///
+	/// The last row is always printed so the end of the dump remains visible.
+	#[inline]
+	pub fn squeeze(mut self, yes: bool) -> HexDump<'a> {
+		self.cfg.squeeze = yes;
+		self
+	}
+	/// Selects the radix of the bytes in the hex column.
+	///
+	/// Every byte is right-justified in a field as wide as the largest byte in the radix to keep the columns aligned.
+	#[inline]
+	pub fn radix(mut self, radix: Radix) -> HexDump<'a> {
+		self.cfg.radix = radix;
+		self
+	}
+	/// Renders zero bytes in the hex column as the replacement glyph so nonzero bytes stand out.
+	///
+	/// Eg. `Some(' ')` leaves zero bytes blank, the ASCII gutter is not affected.
+	#[inline]
+	pub fn dim_zeros(mut self, replacement: Option<char>) -> HexDump<'a> {
+		self.cfg.dim_zeros = replacement;
+		self
+	}
+	/// Shows the bytes equal to the byte at the same index of the template `mask` as `glyph` in both columns.
+	///
+	/// Only the bytes deviating from the template stand out, eg. when comparing against a known-good header.
+	/// The bytes after the end of the mask are not masked.
+	#[inline]
+	pub fn mask(mut self, mask: &'a [u8], glyph: char) -> HexDump<'a> {
+		self.cfg.mask = Some((mask, glyph));
+		self
+	}
+	/// Colors the hex bytes by their class with the ANSI 24-bit foreground escape codes.
+	///
+	/// Only the hex column is colored, the ASCII gutter is not affected.
+	#[inline]
+	pub fn colors(mut self, theme: ColorTheme) -> HexDump<'a> {
+		self.cfg.colors = Some(theme);
+		self
+	}
+	/// Shades the background of every hex byte by the entropy of the 16 bytes around it, from blue to red.
+	///
+	/// Compressed or encrypted data has a high entropy and stands out from uniform data. Uses 24-bit colors like `colors`.
+	#[inline]
+	pub fn entropy_shading(mut self, yes: bool) -> HexDump<'a> {
+		self.cfg.entropy_shading = yes;
+		self
+	}
+	/// Sets the number of spaces between the hex bytes and the ASCII gutter, defaults to 2.
+	#[inline]
+	pub fn gutter_margin(mut self, spaces: usize) -> HexDump<'a> {
+		self.cfg.gutter_margin = spaces;
+		self
+	}
+	/// Prints the offset of each row relative to `base` between the address and the hex bytes.
+	///
+	/// The offsets are signed hex numbers of at least 4 digits, eg. `+0010` or `-0004` for rows before `base`.
+	#[inline]
+	pub fn rel_base(mut self, base: u64) -> HexDump<'a> {
+		self.cfg.rel_base = Some(base);
+		self
+	}
+	/// Shows the `|` around the ASCII gutter, defaults to true.
+	///
+	/// Without them the pipes are replaced by spaces, the glyphs stay in the same columns.
+	#[inline]
+	pub fn gutter_pipes(mut self, yes: bool) -> HexDump<'a> {
+		self.cfg.gutter_pipes = yes;
+		self
+	}
+	/// Sizes the ASCII gutter to the widest row instead of the full 16 columns.
+	#[inline]
+	pub fn compact_gutter(mut self, yes: bool) -> HexDump<'a> {
+		self.cfg.compact_gutter = yes;
+		self
+	}
+	/// Surrounds the dump with the column header and rulers.
+	#[inline]
+	pub fn framed(mut self, yes: bool) -> HexDump<'a> {
+		self.cfg.framed = yes;
+		self
+	}
+	/// Labels byte ranges, relative to the start of the dumped bytes.
+	///
+	/// Each row is followed by a line underlining the bytes of every range it contains.
+	/// The label is printed on the row where its range starts.
+	#[inline]
+	pub fn annotate(mut self, fields: &'a [(Range<usize>, &'a str)]) -> HexDump<'a> {
+		self.cfg.annotations = fields;
+		self
+	}
+	/// Prints a `label:` line before the row containing the address of each label, eg. where a function starts.
+	///
+	/// The addresses are those of the dumped bytes, like the rows before mapping them for display.
+	/// Rows with a label are never squeezed, labels on rows hidden by a filter are not printed.
+	#[inline]
+	pub fn labels(mut self, labels: &'a [(u64, &'a str)]) -> HexDump<'a> {
+		self.cfg.labels = labels;
+		self
+	}
+	/// Returns the canonical form of the dump for golden files committed with tests.
+	///
+	/// Only the bytes and the address of the first byte are used, every other option is ignored. The canonical form
+	/// is stable across versions: rows of 16 lowercase hex bytes with an address of at least 8 digits, the ASCII gutter between
+	/// pipes, no colors or escape sequences, no squeezed rows, no trailing spaces and a `\n` after every line.
+	///
+	/// ```
+	/// use hexdump::hexdump;
+	///
+	/// assert_eq!(hexdump(b"\xC0\xFF\xEE", 0x10).squeeze(true).golden(),
+	/// 	"00000010:  c0 ff ee                                          |...             |\n");
+	/// ```
+	pub fn golden(&self) -> String {
+		let mut dump = HexDump::new(self.bytes, self.offset);
+		dump.cfg.uppercase = false;
+		dump.cfg.no_trailing_ws = true;
+		dump.to_string()
+	}
+	/// Marks the fields of a schema like `magic:4,version:2,payload:*`, starting at the first dumped byte.
+	///
+	/// Each field is a name and its size in bytes, the size `*` of the last field takes the rest of the bytes.
+	/// A `·` in the hex column separates the fields and every field is labeled like the annotations.
+	/// There is no room for the separator between the bytes within a group or in little endian words.
+	///
+	/// ```
+	/// use hexdump::hexdump;
+	///
+	/// let dump = hexdump(b"GIF89a\x20\x00", 0).schema("magic:3,version:3,width:*").unwrap();
+	/// assert_eq!(format!("{}", dump),
+	/// 	"00000000:  47 49 46·38 39 61·20 00                           |GIF89a .        |\n\
+	/// 	\x20          ^^^^^^^^ magic\n\
+	/// 	\x20                   ^^^^^^^^ version\n\
+	/// 	\x20                            ^^^^^ width\n");
+	/// ```
+	pub fn schema(mut self, spec: &'a str) -> Result<HexDump<'a>, BuildError> {
+		self.cfg.schema = schema::parse(spec, self.bytes.len())?;
+		Ok(self)
+	}
+	/// Turns the dump into an iterator over its formatted lines.
+	///
+	/// The iterator owns the dump and only borrows the bytes, it can be stored without keeping the `HexDump` around.
+	#[inline]
+	pub fn into_lines(self) -> IntoLines<'a> {
+		IntoLines::new(self)
+	}
+	/// Labels the fields of a well-known header, see `annotate`.
+	#[inline]
+	pub fn preset(self, preset: Preset<'a>) -> HexDump<'a> {
+		self.annotate(preset.fields())
+	}
+}
+
+/// Column of a row, see `HexDump::columns`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Column {
+	/// The address with its suffix and the relative offset.
+	Address,
+	/// The hex bytes.
+	Hex,
+	/// The ASCII gutter, separated from the column before it by the gutter margin.
+	Ascii,
+	/// The notes after the gutter, like the nonzero counts and the run length notes.
+	Notes,
+}
+impl Column {
+	const DEFAULT: [Column; 4] = [Column::Address, Column::Hex, Column::Ascii, Column::Notes];
 }
 
-const SPACES: &'static str = "|                                                    |";
+/// Radix to print numbers in.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum Radix {
+	/// Hexadecimal, bytes are 2 characters wide.
+	#[default]
+	Hex,
+	/// Decimal, bytes are right-justified to 3 characters.
+	Decimal,
+	/// Octal, bytes are zero padded to 3 characters.
+	Octal,
+	/// Binary, bytes are zero padded to 8 characters.
+	Binary,
+}
+impl Radix {
+	// Number of characters needed for a byte.
+	fn width(self) -> usize {
+		match self {
+			Radix::Hex => 2,
+			Radix::Decimal | Radix::Octal => 3,
+			Radix::Binary => 8,
+		}
+	}
+	fn write(self, f: &mut dyn fmt::Write, byte: u8, uppercase: bool) -> fmt::Result {
+		match self {
+			Radix::Hex if uppercase => write!(f, "{:02X}", byte),
+			Radix::Hex => write!(f, "{:02x}", byte),
+			Radix::Decimal => write!(f, "{:3}", byte),
+			Radix::Octal => write!(f, "{:03o}", byte),
+			Radix::Binary => write!(f, "{:08b}", byte),
+		}
+	}
+}
 
-use ::std::fmt;
+// Character column of the nth byte of a row, relative to the start of the hex bytes.
+// Single bytes are separated by a space with an extra space in the middle, groups of bytes by a space between the groups.
+fn hex_column(n: usize, cell: usize, group: usize) -> usize {
+	if group > 1 { n * cell + n / group }
+	else { n * (cell + 1) + if n >= 8 {1} else {0} }
+}
+// Background color for the byte at the index, by the Shannon entropy of the 16 bytes around it.
+// A window of 16 bytes has at most 4 bits of entropy, shaded from blue for none to red for the maximum.
+fn entropy_shade(bytes: &[u8], index: usize) -> (u8, u8, u8) {
+	let start = cmp::min(index.saturating_sub(8), bytes.len().saturating_sub(16));
+	let mut window = [0u8; 16];
+	let window = &mut window[..cmp::min(16, bytes.len())];
+	window.copy_from_slice(&bytes[start..start + window.len()]);
+	window.sort_unstable();
+	let len = window.len() as f32;
+	let entropy: f32 = window.chunk_by(|a, b| a == b)
+		.map(|run| run.len() as f32 / len)
+		.map(|p| -p * p.log2())
+		.sum();
+	let t = entropy / 4.0;
+	((32.0 + 160.0 * t) as u8, 32, (192.0 - 160.0 * t) as u8)
+}
+// Braille character with a dot for every pair of columns of the row with a nonzero byte.
+// The dots of a braille cell are numbered down the left column and then the right column, with the bottom row last.
+fn braille(skip: usize, bytes: &[u8]) -> char {
+	const DOTS: [u32; 8] = [0x01, 0x02, 0x04, 0x40, 0x08, 0x10, 0x20, 0x80];
+	let mut bits = 0;
+	for (i, &byte) in bytes.iter().enumerate() {
+		if byte != 0 {
+			bits |= DOTS[(skip + i) / 2];
+		}
+	}
+	char::from_u32(0x2800 + bits).unwrap()
+}
+// Columns of the bytes of a row in the order they are shown, as pairs of the column and the index of the byte.
+// Little endian groups show their bytes mirrored within the group, also when the group is only partially in the row.
+fn byte_columns(skip: usize, len: usize, group: usize, little_endian: bool) -> [(usize, usize); 16] {
+	let mut columns = [(0, 0); 16];
+	for (i, slot) in columns.iter_mut().enumerate().take(len) {
+		let column = skip + i;
+		*slot = if little_endian && group > 1 {
+			let start = column - column % group;
+			let end = cmp::min(start + group, 16);
+			(start + end - 1 - column, i)
+		}
+		else {
+			(column, i)
+		};
+	}
+	columns[..len].sort_unstable();
+	columns
+}
+// Spaces before the first and after the last hex byte of a row, the row has `len` bytes starting at column `skip`.
+// Together with the bytes they fill the full width of the hex column regardless of the alignment of the row.
+fn hex_padding(skip: usize, len: usize, cell: usize, group: usize) -> (usize, usize) {
+	let lead = hex_column(skip, cell, group);
+	let end = if len > 0 { hex_column(skip + len - 1, cell, group) + cell } else { lead };
+	(lead, hex_column(15, cell, group) + cell - end)
+}
+// Sign and distance of an address relative to the base.
+fn rel_offset(address: u64, base: u64) -> (char, u64) {
+	if address >= base { ('+', address - base) } else { ('-', base - address) }
+}
+// Number of digits used to print the relative offsets on the addresses.
+fn rel_len(first: u64, last: u64, base: u64) -> usize {
+	let bits = 64 - cmp::max(rel_offset(first, base).1, rel_offset(last, base).1).leading_zeros() as usize;
+	cmp::max(4, bits.div_ceil(4))
+}
+// Tests if the index is in one of the sorted and merged ranges.
+fn is_highlighted(ranges: &[Range<usize>], index: usize) -> bool {
+	let i = ranges.partition_point(|range| range.end <= index);
+	ranges.get(i).is_some_and(|range| range.start <= index)
+}
+// Number of characters used to print an address relative to a segment.
+fn segment_len(name: &str, offset: u64) -> usize {
+	let bits = 64 - offset.leading_zeros() as usize;
+	name.chars().count() + 3 + cmp::max(2, bits.div_ceil(4))
+}
+// Number of characters used to print the address.
+fn addr_len(addr: u64, cfg: &Config) -> usize {
+	let bits = 64 - addr.leading_zeros() as usize;
+	let digits = match cfg.addr_radix {
+		Radix::Hex => bits.div_ceil(4),
+		Radix::Decimal => addr.checked_ilog10().map_or(1, |n| n as usize + 1),
+		Radix::Octal => bits.div_ceil(3),
+		Radix::Binary => bits,
+	};
+	if cfg.groups_addr() { digits + (digits - 1) / 3 }
+	else { cmp::max(cfg.addr_min_width, digits) }
+}
+
+
+const EMPHASIS: &str = "\x1b[7m";
+const RESET: &str = "\x1b[0m";
+const DEFAULT_COLOR: &str = "\x1b[39m";
+const DEFAULT_BACKGROUND: &str = "\x1b[49m";
+
+/// Column header matching the layout of the dumped rows.
+#[inline]
+pub fn header() -> &'static str {
+	"_OFFSET_:  +0 +1 +2 +3 +4 +5 +6 +7  +8 +9 +A +B +C +D +E +F  |___ASCII_DUMP___|"
+}
+/// Ruler separating the dumped rows from their surroundings.
+#[inline]
+pub fn ruler() -> &'static str {
+	"--------:----------------------------------------------------+----------------+"
+}
+/// Column header for rows of `width` bytes, `header_for(16)` is the `header`.
+///
+/// The hex bytes have an extra space every 8 bytes, columns past `+F` are labeled with two hex digits.
+pub fn header_for(width: usize) -> String {
+	let mut header = String::from("_OFFSET_: ");
+	for i in 0..width {
+		if i > 0 && i.is_multiple_of(8) {
+			header.push(' ');
+		}
+		if i < 16 { header.push_str(&format!(" +{:X}", i)); }
+		else { header.push_str(&format!(" {:02X}", i)); }
+	}
+	// Center the longest title which fits in the ASCII gutter
+	let title = ["ASCII_DUMP", "ASCII"].iter().find(|title| title.len() <= width).map_or("", |title| title);
+	let pad = width.saturating_sub(title.len());
+	header.push_str(&format!("  |{:_<3$}{}{:_<4$}|", "", title, "", pad / 2, pad - pad / 2));
+	header
+}
+/// Ruler for rows of `width` bytes, `ruler_for(16)` is the `ruler`.
+pub fn ruler_for(width: usize) -> String {
+	let hex = 3 * width + 3 + width.saturating_sub(1) / 8;
+	format!("--------:{:-<2$}+{:-<3$}+", "", "", hex, width)
+}
+
+use ::std::{cmp, fmt, io, mem};
+use ::std::convert::Infallible;
+use ::std::ops::Range;
+use ::std::rc::Rc;
+use ::std::io::Write;
+use ::std::fmt::Write as _;
 impl<'a> fmt::Display for HexDump<'a> {
 	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-		// Print the hex dump
-		let mut addr = self.offset;
-		while addr < self.bytes.len() + self.offset {
-			// Print offset header
-			try!(write!(f, "{:08X}: ", addr));
+		if self.cfg.no_trailing_ws {
+			return self.write_framed(&mut TrimTrailing { inner: f, spaces: 0 });
+		}
+		self.write_framed(f)
+	}
+}
+impl<'a> HexDump<'a> {
+	// Writes the rows with the header and rulers when framed.
+	fn write_framed(&self, f: &mut dyn fmt::Write) -> fmt::Result {
+		if self.cfg.framed {
+			writeln!(f, "{}", header())?;
+			writeln!(f, "{}", ruler())?;
+		}
+		self.write_rows(f)?;
+		if self.cfg.framed {
+			writeln!(f, "{}", ruler())?;
+		}
+		Ok(())
+	}
+	/// Iterates over the rows of the dump, for custom views of the bytes.
+	///
+	/// The rows are aligned to 16 byte addresses and split at the records, the formatting options are not applied.
+	///
+	/// ```
+	/// use hexdump::hexdump;
+	///
+	/// let row = hexdump(b"hexdump", 0x1C).rows().next().unwrap();
+	/// assert_eq!((row.address(), row.bytes(), row.skip()), (0x1C, &b"hexd"[..], 12));
+	/// ```
+	pub fn rows(&self) -> Rows<'a> {
+		Rows {
+			bytes: self.bytes,
+			offset: self.offset,
+			record_size: self.cfg.record_size,
+			index: 0,
+		}
+	}
+	/// Yields the address, value and glyph of every dumped byte.
+	///
+	/// The glyphs come from the charset and are decoded within a row like the ASCII gutter, the bytes after
+	/// the first of a decoded sequence are spaces. Glyphs which take up several characters, like the caret notation,
+	/// are yielded as `.`.
+	///
+	/// ```
+	/// use hexdump::hexdump;
+	///
+	/// let cells: Vec<_> = hexdump(b"h\x00", 0x10).cells().collect();
+	/// assert_eq!(cells, [(0x10, b'h', 'h'), (0x11, 0x00, '.')]);
+	/// ```
+	pub fn cells(&self) -> impl Iterator<Item = (u64, u8, char)> + 'a {
+		let charset = self.cfg.charset;
+		self.rows().flat_map(move |row| {
+			let mut chars = Vec::new();
+			let Ok(()) = charset.glyphs(row.bytes, |c| -> Result<(), Infallible> { chars.push(c); Ok(()) });
+			let mut glyphs = Vec::with_capacity(row.bytes.len());
+			let mut column = 0;
+			for &byte in row.bytes {
+				let width = charset.width(byte);
+				glyphs.push(if width == 1 { chars[column] } else { '.' });
+				column += width;
+			}
+			row.bytes.iter().zip(glyphs).enumerate()
+				.map(move |(i, (&byte, glyph))| (row.address.wrapping_add(i as u64), byte, glyph))
+		})
+	}
+	/// Finds the byte under a character column of a rendered row.
+	///
+	/// The `row_index` counts the rows of bytes, header and annotation lines are not included.
+	/// Both the hex bytes and the ASCII gutter can be hit, returns the displayed address of the byte,
+	/// including the `display_offset`, or `None` if the column is on the address, a separator or padding.
+	/// The prefix of the row is rendered to find its width, every character of it counts as one column.
+	/// In a nibble grid both digits of a byte hit it.
+	/// A custom column order is only mapped while it starts with the address and the hex bytes,
+	/// the gutter only while it comes right after them. Other layouts return `None`.
+	pub fn hit_test(&self, row_index: usize, char_col: usize) -> Option<u64> {
+		if !self.column_order().starts_with(&[Column::Address, Column::Hex]) {
+			return None;
+		}
+		let row = self.rows().nth(row_index)?;
+		let prefix_len = self.row_prefix(row_index, row.address).chars().count();
+		let hex_start = prefix_len + self.hex_start(self.addr_width(row.address, self.uniform_width()));
+		if self.cfg.nibble_grid {
+			// Two digits for every byte and no gutter
+			let column = char_col.checked_sub(hex_start)? / 2;
+			if column < row.skip || column >= row.skip + row.bytes.len() {
+				return None;
+			}
+			return Some(row.address.wrapping_add((column - row.skip) as u64));
+		}
+		let (cell, group) = (self.cfg.cell_width(), self.cfg.group);
+		let gutter_start = hex_start + hex_column(15, cell, group) + cell + self.cfg.gutter_margin;
+		// Little endian words show their bytes mirrored, the swapped gutter follows the words
+		let columns = byte_columns(row.skip, row.bytes.len(), group, self.cfg.little_endian);
+		let columns = &columns[..row.bytes.len()];
+		let index = if char_col >= gutter_start && !self.cfg.expanded {
+			if self.column_order().get(2) != Some(&Column::Ascii) {
+				return None;
+			}
+			// Walk the glyphs as they may be wider than a single character
+			let swapped = self.cfg.swap_gutter && self.cfg.little_endian;
+			let pads = self.pads();
+			let mut col = gutter_start + if self.cfg.pipes {1} else {0};
+			let mut at = 0;
+			let mut found = None;
+			for (n, &swap) in columns.iter().enumerate() {
+				let (column, i) = if swapped { swap } else { (row.skip + n, n) };
+				if pads {
+					col += column - at;
+				}
+				let width = self.cfg.charset.width(row.bytes[i]);
+				if char_col >= col && char_col < col + width {
+					found = Some(i);
+					break;
+				}
+				col += width;
+				at = column + 1;
+			}
+			found?
+		}
+		else {
+			let col = char_col.checked_sub(hex_start)?;
+			let column = (0..16).find(|&n| col >= hex_column(n, cell, group) && col < hex_column(n, cell, group) + cell)?;
+			columns.iter().find(|&&(at, _)| at == column)?.1
+		};
+		Some(row.address.wrapping_add(index as u64))
+	}
+	// The line number and the custom prefix written before the address of the row.
+	fn row_prefix(&self, row_index: usize, address: u64) -> String {
+		let mut text = String::new();
+		if self.cfg.line_numbers {
+			let _ = write!(text, "{:6} ", row_index + 1);
+		}
+		if let Some(PrefixFn(ref prefix)) = self.cfg.prefix_fn {
+			let _ = write!(text, "{}", fmt::from_fn(|f| prefix(f, row_index, address)));
+		}
+		text
+	}
+	// Order of the columns of a row, the nibble grid keeps the default order.
+	fn column_order(&self) -> &[Column] {
+		if self.cfg.nibble_grid { &Column::DEFAULT } else { &self.cfg.columns }
+	}
+	// Character column where the hex bytes of the row start.
+	fn hex_start(&self, addr_width: usize) -> usize {
+		let rel = match self.cfg.rel_base {
+			Some(_) => self.rel_len() + 2,
+			None => 0,
+		};
+		addr_width + self.cfg.addr_suffix.chars().count() + rel + self.cfg.hex_indent
+	}
+	// Whether the ASCII gutter is padded for the missing bytes of a row.
+	fn pads(&self) -> bool {
+		self.cfg.pad_gutter && self.cfg.charset.pads()
+	}
+	// Number of characters used to print the address of the row.
+	fn addr_width(&self, address: u64, uniform_width: Option<usize>) -> usize {
+		uniform_width.unwrap_or_else(|| addr_len(self.display_addr(address), &self.cfg))
+	}
+	// Width of the widest address when the addresses of the rows are not padded to a common width,
+	// when labeling the rows by segment or grouping the digits.
+	fn uniform_width(&self) -> Option<usize> {
+		if self.cfg.segments.is_empty() && !self.cfg.groups_addr() {
+			return None;
+		}
+		self.rows().map(|row| match self.segment(row.address) {
+			Some((name, offset)) => segment_len(name, offset),
+			None => addr_len(self.display_addr(row.address), &self.cfg),
+		}).max()
+	}
+	// Finds the segment containing the address, returns its name and the offset into it.
+	fn segment(&self, address: u64) -> Option<(&'a str, u64)> {
+		let address = self.display_addr(address);
+		self.cfg.segments.iter()
+			.find(|(range, _)| range.contains(&address))
+			.map(|&(ref range, name)| (name, address - range.start))
+	}
+	fn rel_len(&self) -> usize {
+		self.cfg.rel_len()
+	}
+	fn display_addr(&self, address: u64) -> u64 {
+		(address & self.cfg.addr_mask) | self.cfg.addr_base
+	}
+	/// Returns the highlighted ranges sorted, with overlapping and adjacent ranges merged.
+	///
+	/// Empty ranges are dropped. This is what the dump emphasizes.
+	pub fn effective_highlights(&self) -> Vec<Range<usize>> {
+		let mut ranges: Vec<Range<usize>> = self.cfg.highlights.iter()
+			.filter(|range| range.start < range.end)
+			.cloned()
+			.collect();
+		for pattern in &self.cfg.patterns {
+			ranges.extend(pattern::find(self.bytes, pattern));
+		}
+		ranges.sort_by_key(|range| range.start);
+		let mut merged: Vec<Range<usize>> = Vec::with_capacity(ranges.len());
+		for range in ranges {
+			match merged.last_mut() {
+				Some(last) if range.start <= last.end => last.end = cmp::max(last.end, range.end),
+				_ => merged.push(range),
+			}
+		}
+		merged
+	}
+	// Prepares the state for writing the rows of the dump.
+	fn row_state(&self) -> RowState<'a> {
+		RowState {
+			// Only a dump of a single row can have uniform trailing padding
+			gutter: self.cfg.gutter_width(),
+			cell: self.cfg.cell_width(),
+			rel_len: self.rel_len(),
+			highlights: self.effective_highlights(),
+			uniform_width: self.uniform_width(),
+			row_index: 0,
+			prev: None,
+			squeezing: false,
+			gap: 0,
+			skipped: 0,
+			line: String::new(),
+		}
+	}
+	fn write_rows(&self, f: &mut dyn fmt::Write) -> fmt::Result {
+		let mut state = self.row_state();
+		let mut rows = self.rows().peekable();
+		while let Some(row) = rows.next() {
+			let last = rows.peek().is_none();
+			self.write_row(f, &mut state, row, last)?;
+		}
+		self.finish_rows(f, &mut state)
+	}
+	// Writes the lines for a row, squeezed and collapsed rows are remembered in the state.
+	fn write_row(&self, f: &mut dyn fmt::Write, state: &mut RowState<'a>, row: Row<'a>, last: bool) -> fmt::Result {
+		let (gutter, cell, rel_len) = (state.gutter, state.cell, state.rel_len);
 
-			// Get alignment information
-			let start = addr;
-			let skip = start % 16; // Offset from the left-hand side for this row
-			let end = ::std::cmp::min(start + (16 - skip), self.bytes.len() + self.offset);
-			let skep = 15 - ((end - 1) % 16); // Offset from the right-hand side for this row
-			//println!("offset:{} start:{} skip:{} end:{} skep:{}", self.offset, start, skip, end, skep);
+		if self.cfg.record_size > 0 && row.index > 0 && row.index.is_multiple_of(self.cfg.record_size) {
+			self.write_hidden(f, state)?;
+			writeln!(f, "--- record {} ---", row.index / self.cfg.record_size)?;
+			state.prev = None;
+		}
+		// Only one kind of hidden rows is pending at a time to keep the markers in order
+		if let Some(byte) = self.cfg.filter_contains {
+			if !row.bytes.contains(&byte) {
+				if state.gap > 0 {
+					self.write_hidden(f, state)?;
+				}
+				state.skipped += row.bytes.len();
+				state.prev = None;
+				return Ok(());
+			}
+		}
+		if let Some(fill) = self.cfg.against_fill {
+			if row.bytes.iter().all(|&byte| byte == fill) {
+				if state.skipped > 0 {
+					self.write_hidden(f, state)?;
+				}
+				state.gap += row.bytes.len();
+				state.prev = None;
+				return Ok(());
+			}
+		}
+		self.write_hidden(f, state)?;
+		// Distances from the start of the row as the addresses may wrap around
+		let end = row.address.wrapping_add(row.bytes.len() as u64);
+		let mut labels = self.cfg.labels.iter().filter(|&&(address, _)| address.wrapping_sub(row.address) < end.wrapping_sub(row.address)).peekable();
+		if self.cfg.squeeze {
+			// The last row stays visible unless the final address marks the end, like `hexdump -C`
+			let full = row.bytes.len() == 16;
+			if full && state.prev == Some(row.bytes) && (!last || self.cfg.final_addr) && labels.peek().is_none() {
+				if !state.squeezing {
+					writeln!(f, "*")?;
+					state.squeezing = true;
+				}
+				return Ok(());
+			}
+			state.squeezing = false;
+			state.prev = if full { Some(row.bytes) } else { None };
+		}
+		for &(_, label) in labels {
+			writeln!(f, "{}:", label)?;
+		}
 
-			//----------------------------------------------------------------
-			// HEX BYTES
+		// Print the prefix, its width is needed to align the annotations
+		let row_index = state.row_index;
+		state.row_index += 1;
+		let highlights = &state.highlights;
+		let is_highlighted = |index| is_highlighted(highlights, index);
+		let masked = |i: usize| self.cfg.mask.and_then(|(mask, glyph)| {
+			if mask.get(row.index + i) == Some(&row.bytes[i]) { Some(glyph) } else { None }
+		});
+		let prefix = self.row_prefix(row_index, row.address);
+		f.write_str(&prefix)?;
+		let prefix_len = prefix.chars().count();
 
-			try!(write!(f, "{}", &SPACES[1..2 + skip * 3 + if skip > 8 {1} else {0}]));
-			for (i, byte) in self.bytes[start - self.offset..end - self.offset].iter().enumerate() {
-				// Double space every 8 bytes
-				if skip + i == 8 {
-					try!(write!(f, " "));
+		// The columns are written to the line first to put them in order
+		let mut line = mem::take(&mut state.line);
+		line.clear();
+		let (out, f) = (f, &mut line);
+
+		// Print offset header
+		let addr_width = self.addr_width(row.address, state.uniform_width);
+		let display = self.display_addr(row.address);
+		if let Some(UrlFn(ref url)) = self.cfg.addr_links {
+			write!(f, "\x1b]8;;{}\x1b\\", url(display))?;
+		}
+		let shown = match self.segment(row.address) {
+			Some((name, offset)) => {
+				write!(f, "{}+0x{:02X}", name, offset)?;
+				segment_len(name, offset)
+			},
+			None => {
+				self.write_addr(f, display)?;
+				addr_len(display, &self.cfg)
+			},
+		};
+		if self.cfg.addr_links.is_some() {
+			write!(f, "\x1b]8;;\x1b\\")?;
+		}
+		write!(f, "{}{:2$}", self.cfg.addr_suffix, "", addr_width - shown)?;
+		if let Some(base) = self.cfg.rel_base {
+			let (sign, distance) = rel_offset(row.address, base);
+			write!(f, "{}{:02$X} ", sign, distance, rel_len)?;
+		}
+
+		let hex_at = f.len();
+
+		// Get alignment information
+		let skip = row.skip; // Offset from the left-hand side for this row
+		let skep = 16 - skip - row.bytes.len(); // Offset from the right-hand side for this row
+
+		//----------------------------------------------------------------
+		// HEX BYTES
+
+		let group = self.cfg.group;
+		let ascii_at;
+		if self.cfg.nibble_grid {
+			// Missing bytes are padded to keep the nibbles of every row in the same columns
+			write!(f, "{:1$}", "", self.cfg.hex_indent + skip * 2)?;
+			for &byte in row.bytes {
+				Radix::Hex.write(f, byte, self.cfg.uppercase)?;
+			}
+			write!(f, "{:1$}", "", skep * 2)?;
+			ascii_at = f.len();
+		}
+		else {
+			let columns = byte_columns(skip, row.bytes.len(), group, self.cfg.little_endian);
+			let columns = &columns[..row.bytes.len()];
+			let first = columns.first().map_or(skip, |&(column, _)| column);
+			let span = columns.last().map_or(0, |&(column, _)| column + 1 - first);
+			let (lead, trail) = hex_padding(first, span, cell, group);
+			write!(f, "{:1$}", "", self.cfg.hex_indent + lead)?;
+			let mut emphasis = false;
+			for (n, &(column, i)) in columns.iter().enumerate() {
+				let byte = &row.bytes[i];
+				// Separate the bytes or groups, with a double space every 8 bytes
+				if n > 0 {
+					let gap = hex_column(column, cell, group) - hex_column(columns[n - 1].0, cell, group) - cell;
+					let boundary = !self.cfg.little_endian && self.cfg.schema.iter().any(|(field, _)| field.start == row.index + i);
+					if boundary && gap > 0 {
+						write!(f, "{:1$}·", "", gap - 1)?;
+					}
+					else {
+						write!(f, "{:1$}", "", gap)?;
+					}
+				}
+				if !emphasis && is_highlighted(row.index + i) {
+					emphasis = true;
+					write!(f, "{}", EMPHASIS)?;
+				}
+				let color = self.cfg.colors.and_then(|theme| theme.color(*byte));
+				if let Some((r, g, b)) = color {
+					write!(f, "\x1b[38;2;{};{};{}m", r, g, b)?;
+				}
+				if self.cfg.entropy_shading {
+					let (r, g, b) = entropy_shade(self.bytes, row.index + i);
+					write!(f, "\x1b[48;2;{};{};{}m", r, g, b)?;
+				}
+				match (masked(i), self.cfg.dim_zeros) {
+					(Some(glyph), _) => for _ in 0..self.cfg.radix.width() { write!(f, "{}", glyph)?; },
+					(None, Some(c)) if *byte == 0 => for _ in 0..self.cfg.radix.width() { write!(f, "{}", c)?; },
+					_ => self.cfg.radix.write(f, *byte, self.cfg.uppercase)?,
+				}
+				if self.cfg.expanded {
+					write!(f, "(")?;
+					match masked(i) {
+						Some(glyph) => write!(f, "{}", glyph)?,
+						None => self.cfg.charset.write(f, ::std::slice::from_ref(byte))?,
+					}
+					write!(f, ")")?;
+				}
+				if color.is_some() {
+					write!(f, "{}", DEFAULT_COLOR)?;
+				}
+				if self.cfg.entropy_shading {
+					write!(f, "{}", DEFAULT_BACKGROUND)?;
+				}
+				// Keep the emphasis going between highlighted bytes
+				if emphasis && !columns.get(n + 1).is_some_and(|&(_, next)| is_highlighted(row.index + next)) {
+					emphasis = false;
+					write!(f, "{}", RESET)?;
 				}
-				try!(write!(f, "{:02X} ", byte));
 			}
-			try!(write!(f, "{}", &SPACES[1..2 + skep * 3 + if skep >= 8 {1} else {0}]));
+			ascii_at = f.len() + if self.cfg.expanded { 0 } else { trail };
+			// The bytes of an expanded row carry their own glyphs
+			if !self.cfg.expanded {
+				write!(f, "{:1$}", "", trail + self.cfg.gutter_margin)?;
 
-			//----------------------------------------------------------------
-			// ASCII BYTES
+				//----------------------------------------------------------------
+				// ASCII BYTES
 
-			try!(write!(f, "{}", &SPACES[0..1 + skip]));
-			for &byte in &self.bytes[start - self.offset..end - self.offset] {
-				let c = if byte < 0x20 || byte >= 0x80 { '.' }
-				else { unsafe { ::std::char::from_u32_unchecked(byte as u32) } };
-				try!(write!(f, "{}", c));
+				let pads = self.pads();
+				let pipe = match (self.cfg.pipes, self.cfg.gutter_pipes) {
+					(false, _) => "",
+					(true, true) => "|",
+					(true, false) => " ",
+				};
+				if self.cfg.swap_gutter && self.cfg.little_endian {
+					// Every glyph at the column of its hex byte
+					write!(f, "{}", pipe)?;
+					let mut at = 0;
+					for &(column, i) in columns {
+						if pads {
+							write!(f, "{:1$}", "", column - at)?;
+						}
+						let emphasis = is_highlighted(row.index + i);
+						if emphasis {
+							write!(f, "{}", EMPHASIS)?;
+						}
+						match masked(i) {
+							Some(glyph) => write!(f, "{}", glyph)?,
+							None => self.cfg.charset.write(f, &row.bytes[i..i + 1])?,
+						}
+						if emphasis {
+							write!(f, "{}", RESET)?;
+						}
+						at = column + 1;
+					}
+					let pad = if pads { gutter.saturating_sub(at) } else { 0 };
+					write!(f, "{:1$}{2}", "", pad, pipe)?;
+				}
+				else {
+					write!(f, "{}{:2$}", pipe, "", if pads { skip } else { 0 })?;
+					let mut i = 0;
+					while i < row.bytes.len() {
+						let (emphasis, glyph) = (is_highlighted(row.index + i), masked(i));
+						let mut end = i + 1;
+						while end < row.bytes.len() && is_highlighted(row.index + end) == emphasis && masked(end) == glyph {
+							end += 1;
+						}
+						if emphasis {
+							write!(f, "{}", EMPHASIS)?;
+						}
+						match glyph {
+							Some(glyph) => for _ in i..end { write!(f, "{}", glyph)?; },
+							None => self.cfg.charset.write(f, &row.bytes[i..end])?,
+						}
+						if emphasis {
+							write!(f, "{}", RESET)?;
+						}
+						i = end;
+					}
+					let pad = if pads { gutter - (16 - skep) } else { 0 };
+					write!(f, "{:1$}{2}", "", pad, pipe)?;
+				}
+			}
+		}
+
+		//----------------------------------------------------------------
+		// RUN LENGTH NOTES
+
+		let notes_at = f.len();
+		if self.cfg.braille_overview {
+			write!(f, " {}", braille(row.skip, row.bytes))?;
+		}
+		if self.cfg.nonzero_count {
+			write!(f, " [nz={}]", row.bytes.iter().filter(|&&byte| byte != 0).count())?;
+		}
+		if self.cfg.rle_note > 0 {
+			let mut address = row.address;
+			for run in row.bytes.chunk_by(|a, b| a == b) {
+				if run.len() >= self.cfg.rle_note {
+					write!(f, " ({:02X} x{} @0x{:X})", run[0], run.len(), self.display_addr(address))?;
+				}
+				address = address.wrapping_add(run.len() as u64);
 			}
-			try!(write!(f, "{}", &SPACES[SPACES.len() - (1 + skep)..]));
+		}
 
-			//----------------------------------------------------------------
+		//----------------------------------------------------------------
 
-			// Newline and advance
-			try!(write!(f, "\n"));
-			addr = end;
+		let sections = [0..hex_at, hex_at..ascii_at, ascii_at..notes_at, notes_at..line.len()];
+		for (n, &column) in self.column_order().iter().enumerate() {
+			let text = &line[sections[column as usize].clone()];
+			// Without a column before it the gutter has no margin
+			let text = if column == Column::Ascii && n == 0 { text.get(self.cfg.gutter_margin..).unwrap_or("") } else { text };
+			out.write_str(text)?;
+		}
+		state.line = line;
+		let f = out;
+
+		// Newline and advance
+		writeln!(f)?;
+
+		//----------------------------------------------------------------
+		// ANNOTATIONS
+
+		let hex_start = match *self.column_order() {
+			[Column::Hex, ..] => self.cfg.hex_indent,
+			[Column::Address, Column::Hex, ..] => self.hex_start(addr_width),
+			// Nowhere to align them with
+			_ => return Ok(()),
+		};
+		let (lo, hi) = (row.index, row.index + row.bytes.len());
+		for &(ref range, label) in self.cfg.annotations.iter().chain(&self.cfg.schema) {
+			let from = cmp::max(range.start, lo);
+			let to = cmp::min(range.end, hi);
+			if from >= to {
+				continue;
+			}
+			let first = hex_column(skip + from - lo, cell, group);
+			let last = hex_column(skip + to - 1 - lo, cell, group) + cell;
+			write!(f, "{:1$}", "", prefix_len + hex_start + first)?;
+			for _ in first..last {
+				write!(f, "^")?;
+			}
+			if range.start >= lo {
+				write!(f, " {}", label)?;
+			}
+			writeln!(f)?;
+		}
+		Ok(())
+	}
+	// Writes what is still pending after the last row.
+	fn finish_rows(&self, f: &mut dyn fmt::Write, state: &mut RowState<'a>) -> fmt::Result {
+		self.write_hidden(f, state)?;
+		if self.cfg.final_addr && !self.bytes.is_empty() {
+			self.write_addr(f, self.display_addr(self.offset.wrapping_add(self.bytes.len() as u64)))?;
+			writeln!(f)?;
+		}
+		if self.cfg.histogram > 0 && !self.bytes.is_empty() {
+			let mut counts = [0usize; 256];
+			for &byte in self.bytes {
+				counts[byte as usize] += 1;
+			}
+			let mut values: Vec<usize> = (0..256).filter(|&value| counts[value] > 0).collect();
+			values.sort_by_key(|&value| cmp::Reverse(counts[value]));
+			write!(f, "; histogram:")?;
+			for (i, &value) in values.iter().take(self.cfg.histogram).enumerate() {
+				write!(f, "{} {:02X} x{}", if i > 0 { "," } else { "" }, value, counts[value])?;
+			}
+			writeln!(f)?;
+		}
+		Ok(())
+	}
+	fn write_addr(&self, f: &mut dyn fmt::Write, address: u64) -> fmt::Result {
+		let width = self.cfg.addr_min_width;
+		match self.cfg.addr_radix {
+			Radix::Hex if self.cfg.uppercase => write!(f, "{:01$X}", address, width),
+			Radix::Hex => write!(f, "{:01$x}", address, width),
+			Radix::Decimal if self.cfg.addr_grouping => {
+				let digits = address.to_string();
+				for (i, digit) in digits.chars().enumerate() {
+					if i > 0 && (digits.len() - i).is_multiple_of(3) {
+						f.write_char(',')?;
+					}
+					f.write_char(digit)?;
+				}
+				Ok(())
+			},
+			Radix::Decimal => write!(f, "{:01$}", address, width),
+			Radix::Octal => write!(f, "{:01$o}", address, width),
+			Radix::Binary => write!(f, "{:01$b}", address, width),
+		}
+	}
+	// Writes the markers for the rows hidden since the last written row.
+	fn write_hidden(&self, f: &mut dyn fmt::Write, state: &mut RowState<'a>) -> fmt::Result {
+		if let (Some(fill), true) = (self.cfg.against_fill, state.gap > 0) {
+			writeln!(f, "; ---- {} bytes of {:02X} ----", state.gap, fill)?;
+			state.gap = 0;
+		}
+		if state.skipped > 0 {
+			writeln!(f, "; ---- {} bytes skipped ----", state.skipped)?;
+			state.skipped = 0;
 		}
 		Ok(())
 	}
 }
 
-#[inline]
-pub fn hexdump(bytes: &[u8], offset: usize) -> HexDump {
-	HexDump {
-		bytes: bytes,
-		offset: offset,
+// Drops the spaces at the end of every line written through it.
+struct TrimTrailing<'f> {
+	inner: &'f mut dyn fmt::Write,
+	spaces: usize, // Spaces held back until something else follows them on the line
+}
+impl<'f> fmt::Write for TrimTrailing<'f> {
+	fn write_str(&mut self, s: &str) -> fmt::Result {
+		for (i, line) in s.split('\n').enumerate() {
+			if i > 0 {
+				self.spaces = 0;
+				self.inner.write_char('\n')?;
+			}
+			let text = line.trim_end_matches(' ');
+			if !text.is_empty() {
+				write!(self.inner, "{:1$}{2}", "", self.spaces, text)?;
+				self.spaces = 0;
+			}
+			self.spaces += line.len() - text.len();
+		}
+		Ok(())
+	}
+}
+
+// Settings and state carried between the rows while writing them.
+struct RowState<'a> {
+	gutter: usize, // Width of the ASCII gutter
+	cell: usize, // Width of a hex byte
+	rel_len: usize, // Digits of the relative offsets
+	highlights: Vec<Range<usize>>,
+	uniform_width: Option<usize>, // Width of the addresses padded to the widest
+	row_index: usize,
+	prev: Option<&'a [u8]>, // Previous full row for squeezing
+	squeezing: bool,
+	gap: usize, // Bytes of fill collapsed so far
+	skipped: usize, // Bytes of rows without the filtered byte collapsed so far
+	line: String, // Columns of the row before putting them in order
+}
+
+/// A single row of the dump, see `HexDump::rows`.
+#[derive(Copy, Clone, Debug)]
+pub struct Row<'a> {
+	address: u64, // Address of the first byte in this row
+	skip: usize, // Columns left empty before the first byte
+	index: usize, // Index of the first byte in the dumped bytes
+	bytes: &'a [u8],
+}
+impl<'a> Row<'a> {
+	/// Address of the first byte of the row.
+	#[inline]
+	pub fn address(&self) -> u64 {
+		self.address
+	}
+	/// The bytes of the row.
+	#[inline]
+	pub fn bytes(&self) -> &'a [u8] {
+		self.bytes
+	}
+	/// Number of columns left empty before the first byte to align the row to 16 bytes.
+	#[inline]
+	pub fn skip(&self) -> usize {
+		self.skip
+	}
+	/// Writes the hex bytes in the default layout, padded to the full width of the hex column.
+	pub fn write_hex(&self, f: &mut dyn fmt::Write) -> fmt::Result {
+		let (lead, trail) = hex_padding(self.skip, self.bytes.len(), 2, 1);
+		write!(f, "{:1$}", "", lead)?;
+		for (i, byte) in self.bytes.iter().enumerate() {
+			if i > 0 {
+				write!(f, "{:1$}", "", hex_column(self.skip + i, 2, 1) - hex_column(self.skip + i - 1, 2, 1) - 2)?;
+			}
+			write!(f, "{:02X}", byte)?;
+		}
+		write!(f, "{:1$}", "", trail)
 	}
+	/// Writes the glyphs of the bytes in the charset, padded to 16 columns like the ASCII gutter.
+	pub fn write_ascii(&self, f: &mut dyn fmt::Write, charset: Charset) -> fmt::Result {
+		write!(f, "{:1$}", "", self.skip)?;
+		charset.write(f, self.bytes)?;
+		write!(f, "{:1$}", "", 16 - self.skip - self.bytes.len())
+	}
+	/// Writes the row as a JSON object on a single line, eg. `{"offset":16,"hex":"45 72 72","ascii":"Err"}`.
+	///
+	/// The hex bytes and glyphs are not padded, the glyphs are escaped as a JSON string.
+	pub fn write_json(&self, f: &mut dyn fmt::Write, charset: Charset) -> fmt::Result {
+		write!(f, "{{\"offset\":{},\"hex\":\"", self.address)?;
+		for (i, byte) in self.bytes.iter().enumerate() {
+			write!(f, "{}{:02X}", if i > 0 { " " } else { "" }, byte)?;
+		}
+		f.write_str("\",\"ascii\":\"")?;
+		charset.glyphs(self.bytes, |c| match c {
+			'"' => f.write_str("\\\""),
+			'\\' => f.write_str("\\\\"),
+			_ if c.is_control() => write!(f, "\\u{:04x}", c as u32),
+			_ => f.write_char(c),
+		})?;
+		f.write_str("\"}")
+	}
+}
+
+/// Iterator over the rows of a dump, see `HexDump::rows`.
+// Splits the dumped bytes in rows aligned to 16 byte addresses.
+// Addresses wrap around instead of overflowing, alignment is preserved as 2^64 is a multiple of 16.
+#[derive(Clone, Debug)]
+pub struct Rows<'a> {
+	bytes: &'a [u8],
+	offset: u64,
+	record_size: usize,
+	index: usize,
+}
+impl<'a> Iterator for Rows<'a> {
+	type Item = Row<'a>;
+	fn next(&mut self) -> Option<Row<'a>> {
+		let rest = &self.bytes[self.index..];
+		if rest.is_empty() {
+			return None;
+		}
+		let address = self.offset.wrapping_add(self.index as u64);
+		let skip = (address % 16) as usize;
+		let mut len = cmp::min(16 - skip, rest.len());
+		if self.record_size > 0 {
+			len = cmp::min(len, self.record_size - self.index % self.record_size);
+		}
+		let row = Row {
+			address,
+			skip,
+			index: self.index,
+			bytes: &rest[..len],
+		};
+		self.index += len;
+		Some(row)
+	}
+}
+
+#[inline]
+pub fn hexdump(bytes: &[u8], offset: usize) -> HexDump<'_> {
+	HexDump::new(bytes, offset as u64)
+}
+/// Dumps any buffer which can be viewed as bytes.
+///
+/// ```
+/// use hexdump::hexdump_ref;
+///
+/// let string = String::from("hexdump");
+/// assert_eq!(format!("{}", hexdump_ref(&string, 0)),
+/// 	"00000000:  68 65 78 64 75 6D 70                              |hexdump         |\n");
+///
+/// let boxed: Box<[u8]> = Box::new([0xDE, 0xAD, 0xBE, 0xEF]);
+/// assert_eq!(format!("{}", hexdump_ref(&boxed, 4)),
+/// 	"00000004:              DE AD BE EF                           |    ....        |\n");
+/// ```
+#[inline]
+pub fn hexdump_ref<T: AsRef<[u8]> + ?Sized>(t: &T, offset: usize) -> HexDump<'_> {
+	hexdump(t.as_ref(), offset)
+}
+/// Dumps `len` bytes starting at `start_index`, the first of them is labeled with the address `start_addr`.
+///
+/// Panics if the range is out of bounds of the bytes.
+///
+/// ```
+/// use hexdump::hexdump_range;
+///
+/// let memory = b"....hexdump....";
+/// assert_eq!(format!("{}", hexdump_range(memory, 0x7FF0_0010, 4, 7)),
+/// 	"7FF00010:  68 65 78 64 75 6D 70                              |hexdump         |\n");
+/// ```
+#[inline]
+pub fn hexdump_range(bytes: &[u8], start_addr: u64, start_index: usize, len: usize) -> HexDump<'_> {
+	HexDump::new(&bytes[start_index..start_index + len], start_addr)
+}
+#[inline]
+pub fn datadump<T>(data: &T) -> HexDump<'_> {
+	let bytes = unsafe {
+		::std::slice::from_raw_parts(
+			data as *const T as *const u8,
+			::std::mem::size_of_val(data))
+	};
+	HexDump::new(bytes, 0)
+}
+/// Dumps the memory of `data` with labeled byte ranges.
+///
+/// The ranges are offsets in the byte layout of `T`, use `#[repr(C)]` to know them.
+#[inline]
+pub fn datadump_annotated<'a, T>(data: &'a T, fields: &'a [(Range<usize>, &'a str)]) -> HexDump<'a> {
+	datadump(data).annotate(fields)
+}
+/// Dumps the memory in `[start, end)`, the bytes are labeled with their addresses.
+///
+/// # Safety
+///
+/// Both pointers must be into, or one past the end of, the same allocation with `start` not after `end`.
+/// The whole range must be initialized memory which is not written to while the dump is alive.
+/// The lifetime of the dump is unbounded, it must not outlive the memory.
+///
+/// ```
+/// use hexdump::hexdump_ptr_range;
+///
+/// let bytes = *b"hexdump";
+/// let range = bytes.as_ptr_range();
+/// let dump = unsafe { hexdump_ptr_range(range.start, range.end) };
+/// assert!(format!("{}", dump).contains("68 65 78 64 75 6D 70"));
+/// ```
+#[inline]
+pub unsafe fn hexdump_ptr_range(start: *const u8, end: *const u8) -> HexDump<'static> {
+	let bytes = ::std::slice::from_raw_parts(start, end as usize - start as usize);
+	HexDump::new(bytes, start as usize as u64)
 }
+
+/// Writes the hex dump to an `io::Write` stream, formatted by the config like `HexDump::with_config`.
 #[inline]
-pub fn datadump<T>(data: &T) -> HexDump {
-	HexDump {
-		bytes: unsafe {
-			::std::slice::from_raw_parts(
-				data as *const T as *const u8,
-				::std::mem::size_of_val(data))
-		},
-		offset: 0,
+pub fn hexdump_to<'a, W: io::Write>(mut w: W, bytes: &'a [u8], offset: usize, cfg: &Config<'a>) -> io::Result<()> {
+	write!(w, "{}", hexdump(bytes, offset).with_config(cfg.clone()))
+}
+/// Streams the hex dump of everything read from `reader`, the first byte is at `offset`.
+///
+/// Holds a single row in memory, with `squeeze` the output matches `HexDump::squeeze`.
+pub fn hexdump_reader<R: io::Read, W: io::Write>(mut reader: R, mut writer: W, offset: u64, squeeze: bool) -> io::Result<()> {
+	let mut address = offset;
+	let mut prev = [0u8; 16];
+	let mut row = [0u8; 16];
+	let mut has_prev = false;
+	let mut held: Option<u64> = None;
+	loop {
+		let skip = (address % 16) as usize;
+		let len = fill(&mut reader, &mut row[skip..])?;
+		if len == 0 {
+			break;
+		}
+		let full = len == 16;
+		if squeeze && full && has_prev && row == prev {
+			if held.is_none() {
+				writeln!(writer, "*")?;
+			}
+			// Hold back the duplicate row in case it is the last
+			held = Some(address);
+		}
+		else {
+			held = None;
+			write!(writer, "{}", HexDump::new(&row[skip..skip + len], address))?;
+			has_prev = full;
+			prev = row;
+		}
+		address = address.wrapping_add(len as u64);
 	}
+	if let Some(address) = held {
+		write!(writer, "{}", HexDump::new(&prev, address))?;
+	}
+	Ok(())
+}
+fn fill<R: io::Read>(reader: &mut R, buf: &mut [u8]) -> io::Result<usize> {
+	let mut len = 0;
+	while len < buf.len() {
+		match reader.read(&mut buf[len..]) {
+			Ok(0) => break,
+			Ok(n) => len += n,
+			Err(ref err) if err.kind() == io::ErrorKind::Interrupted => (),
+			Err(err) => return Err(err),
+		}
+	}
+	Ok(len)
+}
+/// Writes the hex dump like `hexdump_to` through a `BufWriter` of the given capacity, flushing it at the end.
+pub fn hexdump_buffered<'a, W: io::Write>(w: W, bytes: &'a [u8], offset: usize, cfg: &Config<'a>, cap: usize) -> io::Result<()> {
+	let mut w = io::BufWriter::with_capacity(cap, w);
+	hexdump_to(&mut w, bytes, offset, cfg)?;
+	w.flush()
 }
 
 #[cfg(test)]
 mod tests {
 	use super::*;
 
-	const BYTES: &'static [u8] = b"\x48\x83\xEC\x28\xE8\x1B\x03\x00\x00\x48\x83\xC4\x28\xE9\x66\xFE\
+	const BYTES: &[u8] = b"\x48\x83\xEC\x28\xE8\x1B\x03\x00\x00\x48\x83\xC4\x28\xE9\x66\xFE\
 	                               \x45\x72\x72\x6F\x72\x20\x63\x6F\x64\x65\x20\x00\x00\x00\x00\x00\
 								   \x00\x11\x22\x33\x44\x55\x66\x77\x88\x99\xAA\xBB\xCC\xDD\xEE\xFF";
 
 	fn header() {
-		print!("_OFFSET_:  +0 +1 +2 +3 +4 +5 +6 +7  +8 +9 +A +B +C +D +E +F  |___ASCII_DUMP___|\n\
-		        --------:----------------------------------------------------+----------------+\n")
+		println!("{}\n{}", super::header(), ruler());
+	}
+
+	#[test]
+	fn header_for() {
+		assert_eq!(super::header_for(16), super::header());
+		assert_eq!(super::ruler_for(16), ruler());
+		assert_eq!(super::header_for(8), "_OFFSET_:  +0 +1 +2 +3 +4 +5 +6 +7  |_ASCII__|");
+		assert_eq!(super::header_for(8).len(), super::ruler_for(8).len());
+		assert_eq!(super::ruler_for(8).find('+'), super::header_for(8).find('|'));
+		let header = super::header_for(32);
+		assert!(header.contains("+F  10 11"));
+		assert!(header.ends_with("1F  |___________ASCII_DUMP___________|"));
+		assert_eq!(header.len(), super::ruler_for(32).len());
+		assert_eq!(super::ruler_for(32).find('+'), header.find('|'));
+		// The default width lines up with the dumped rows
+		let row = format!("{}", hexdump(&BYTES[32..48], 32));
+		assert_eq!(row.trim_end().len(), super::ruler_for(16).len());
+		assert_eq!(row.find('|'), super::ruler_for(16).find('+'));
 	}
 
 	#[test]
@@ -144,4 +1770,927 @@ mod tests {
 			"00000008:                           00 48 83 C4 28 E9 66 FE  |        .H..(.f.|\n\
 			 00000010:  45 72 72 6F 72 20 63 6F                           |Error co        |\n");
 	}
+
+	#[test]
+	fn hexdump_range() {
+		assert_eq!(format!("{}", super::hexdump_range(BYTES, 0x401000, 14, 6)),
+			"00401000:  66 FE 45 72 72 6F                                 |f.Erro          |\n");
+		// The range is aligned to the address, not to the index
+		assert_eq!(format!("{}", super::hexdump_range(BYTES, 0x40100C, 0, 6)), format!("{}", hexdump(&BYTES[..6], 0x40100C)));
+	}
+
+	#[test]
+	fn hexdump_ptr_range() {
+		let memory: Vec<u8> = BYTES.to_vec();
+		let range = memory[14..20].as_ptr_range();
+		let dump = format!("{}", unsafe { super::hexdump_ptr_range(range.start, range.end) });
+		assert_eq!(dump, format!("{}", hexdump_ref(&memory[14..20], range.start as usize)));
+		assert!(dump.starts_with(&format!("{:08X}:", range.start as usize)));
+	}
+
+	#[test]
+	fn hexdump_ring() {
+		// The oldest byte is at 0x14, the newest just before it
+		let ring = super::hexdump_ring(&BYTES[..32], 0x14, 0x1C, 0x100);
+		assert_eq!(ring.bytes().len(), 0x1C);
+		assert_eq!(ring.bytes()[..8], BYTES[0x14..0x1C]);
+		assert_eq!(ring.bytes()[8..], BYTES[..0x14]);
+		assert_eq!(format!("{}", ring).lines().collect::<Vec<_>>(), [
+			"00000100:  72 20 63 6F 64 65 20 00  48 83 EC 28 E8 1B 03 00  |r code .H..(....|",
+			"00000110:  00 48 83 C4 28 E9 66 FE  45 72 72 6F              |.H..(.f.Erro    |",
+		]);
+		// A head at the start or at the capacity does not wrap
+		assert_eq!(super::hexdump_ring(BYTES, 0, 8, 0), super::hexdump_ring(BYTES, 8, 8, 0));
+	}
+
+	#[test]
+	fn labels() {
+		let labels = [(0x1004, "main"), (0x1020, "data"), (0x1013, "helper")];
+		assert_eq!(format!("{}", hexdump(&BYTES[..32], 0x1000).labels(&labels)).lines().collect::<Vec<_>>(), [
+			"main:",
+			"00001000:  48 83 EC 28 E8 1B 03 00  00 48 83 C4 28 E9 66 FE  |H..(.....H..(.f.|",
+			"helper:",
+			"00001010:  45 72 72 6F 72 20 63 6F  64 65 20 00 00 00 00 00  |Error code .....|",
+		]);
+		// A label keeps its row from being squeezed
+		let zeros = [0u8; 48];
+		assert_eq!(format!("{}", hexdump(&zeros, 0x1000).labels(&labels).squeeze(true)).lines().collect::<Vec<_>>(), [
+			"main:",
+			"00001000:  00 00 00 00 00 00 00 00  00 00 00 00 00 00 00 00  |................|",
+			"helper:",
+			"00001010:  00 00 00 00 00 00 00 00  00 00 00 00 00 00 00 00  |................|",
+			"data:",
+			"00001020:  00 00 00 00 00 00 00 00  00 00 00 00 00 00 00 00  |................|",
+		]);
+		// Labels which look like addresses are not mistaken for rows
+		for labels in &[[(0, "cafe"), (0x10, "10")], [(0x10, "cafe"), (0x14, "beef")]] {
+			let dump = format!("{}", hexdump(&BYTES[..32], 0).labels(labels));
+			assert_eq!(super::unhexdump(&dump, None), Ok(BYTES[..32].to_vec()));
+			assert_eq!(super::unhexdump_stream(dump.chars()).collect::<Result<Vec<u8>, _>>(), Ok(BYTES[..32].to_vec()));
+		}
+	}
+
+	#[test]
+	fn annotated() {
+		#[allow(dead_code)]
+		#[repr(C)]
+		struct Pair {
+			tag: u32,
+			value: u32,
+		}
+		let pair = Pair { tag: 1, value: 42 };
+		let dump = format!("{}", datadump_annotated(&pair, &[(0..4, "discriminant"), (4..8, "payload")]));
+		assert_eq!(dump.lines().collect::<Vec<_>>(), [
+			"00000000:  01 00 00 00 2A 00 00 00                           |....*...        |",
+			"           ^^^^^^^^^^^ discriminant",
+			"                       ^^^^^^^^^^^ payload",
+		]);
+		// Ranges spanning rows are underlined on every row, labeled once
+		let dump = format!("{}", hexdump(&BYTES[12..20], 12).annotate(&[(2..6, "split")]));
+		assert_eq!(dump.lines().collect::<Vec<_>>(), [
+			"0000000C:                                       28 E9 66 FE  |            (.f.|",
+			"                                                      ^^^^^ split",
+			"00000010:  45 72 72 6F                                       |Erro            |",
+			"           ^^^^^",
+		]);
+	}
+
+	#[test]
+	fn preset() {
+		let header = b"\x45\x00\x00\x54\x12\x34\x40\x00\x40\x01\x00\x00\x0A\x00\x00\x01\x0A\x00\x00\x02";
+		let dump = format!("{}", hexdump(header, 0).preset(Preset::Ipv4));
+		assert!(dump.lines().any(|line| line.ends_with("^^^^^ total length")));
+		assert_eq!(dump.lines().filter(|line| line.ends_with(" dst addr")).count(), 1);
+		let fields = &[(0..2, "magic")];
+		assert_eq!(Preset::Custom(fields).fields(), fields);
+	}
+
+	#[test]
+	fn alignment() {
+		// Every partial row is the full row with the missing bytes blanked out, after the address
+		let full: Vec<char> = format!("{}", hexdump(&BYTES[..16], 0)).chars().collect();
+		for skip in 0..16 {
+			for len in 1..=16 - skip {
+				let expected: String = full.iter().enumerate().skip(8).map(|(col, &c)| {
+					let hex = (0..16).find(|&n| (11 + hex_column(n, 2, 1)..13 + hex_column(n, 2, 1)).contains(&col));
+					let glyph = if (62..78).contains(&col) { Some(col - 62) } else { None };
+					match hex.or(glyph) {
+						Some(n) if n < skip || n >= skip + len => ' ',
+						_ => c,
+					}
+				}).collect();
+				let dump = hexdump(&BYTES[skip..skip + len], skip);
+				assert_eq!(format!("{}", dump)[8..], expected, "skip {} len {}", skip, len);
+				let mut buf = [0u8; 80];
+				let n = super::format_row_into(&mut buf, skip as u64, &BYTES[skip..skip + len], dump.config()).unwrap();
+				assert_eq!(&buf[8..n], expected.trim_end_matches('\n').as_bytes());
+			}
+		}
+		for cell in &[2, 3, 8] {
+			for skip in 0..16 {
+				for group in 1..5 {
+					let (lead, trail) = hex_padding(skip, 16 - skip, *cell, group);
+					assert_eq!(lead, hex_column(skip, *cell, group));
+					assert_eq!(trail, 0);
+					assert_eq!(hex_padding(skip, 0, *cell, group), (lead, hex_column(15, *cell, group) + *cell - lead));
+				}
+			}
+		}
+	}
+
+	#[test]
+	fn large_offset() {
+		assert_eq!(format!("{}", hexdump(&BYTES[32..48], 0).display_offset(u64::MAX - 32)),
+			"FFFFFFFFFFFFFFDF:                                                00  |               .|\n\
+			 FFFFFFFFFFFFFFE0:  11 22 33 44 55 66 77 88  99 AA BB CC DD EE FF     |.\"3DUfw........ |\n");
+		// Addresses wrap around the end of the address space
+		assert_eq!(format!("{}", hexdump(&BYTES[32..48], 0).display_offset(u64::MAX - 3)).lines().nth(1),
+			Some("00000000:  44 55 66 77 88 99 AA BB  CC DD EE FF              |DUfw........    |"));
+	}
+
+	#[test]
+	fn addr_map() {
+		let dump = format!("{}", hexdump(&BYTES[..20], 0).addr_map(0x40000000, 0xFFF));
+		assert_eq!(dump.lines().map(|line| &line[..9]).collect::<Vec<_>>(), ["40000000:", "40000010:"]);
+		let dump = format!("{}", hexdump(&BYTES[..4], 0x1FF8).addr_map(0x7FFE0000, 0xFFFF));
+		assert_eq!(&dump[..9], "7FFE1FF8:");
+	}
+
+	#[test]
+	fn addr_min_width() {
+		let dump = hexdump(&BYTES[..4], 0x1C).addr_min_width(4).annotate(&[(1..3, "x")]);
+		assert_eq!(format!("{}", dump).lines().collect::<Vec<_>>(), [
+			"001C:                                       48 83 EC 28  |            H..(|",
+			"                                               ^^^^^ x",
+		]);
+		// Addresses which need more digits are not truncated
+		let dump = format!("{}", hexdump(&BYTES[..4], 0x12345).addr_min_width(4));
+		assert_eq!(&dump[..7], "12345: ");
+	}
+
+	#[test]
+	fn addr_radix() {
+		let dump = hexdump(&BYTES[..20], 999_990).addr_radix(Radix::Decimal).addr_grouping(true);
+		assert_eq!(format!("{}", dump).lines().collect::<Vec<_>>(), [
+			"999,990:                      48 83  EC 28 E8 1B 03 00 00 48  |      H..(.....H|",
+			"1,000,000:  83 C4 28 E9 66 FE 45 72  72 6F                    |..(.f.Erro      |",
+		]);
+		let mut buf = [0u8; 80];
+		let len = super::format_row_into(&mut buf, 999_990, &BYTES[..10], dump.config()).unwrap();
+		assert_eq!(&buf[..10], b"999,990:  ");
+		assert_eq!(len, 78);
+		// Without grouping the addresses are zero padded
+		assert_eq!(&format!("{}", hexdump(&BYTES[..4], 1234).addr_radix(Radix::Decimal))[..9], "00001234:");
+		assert_eq!(&format!("{}", hexdump(&BYTES[..4], 32).addr_radix(Radix::Octal))[..9], "00000040:");
+	}
+
+	#[test]
+	fn addr_links() {
+		let dump = hexdump(&BYTES[12..20], 12).addr_links(|address| format!("file:///dump.bin#{}", address)).annotate(&[(4..6, "x")]);
+		let text = format!("{}", dump);
+		assert_eq!(text.lines().collect::<Vec<_>>(), [
+			"\x1b]8;;file:///dump.bin#12\x1b\\0000000C\x1b]8;;\x1b\\:                                       28 E9 66 FE  |            (.f.|",
+			"\x1b]8;;file:///dump.bin#16\x1b\\00000010\x1b]8;;\x1b\\:  45 72 72 6F                                       |Erro            |",
+			"           ^^^^^ x",
+		]);
+		// The links do not get in the way of reversing the dump
+		assert_eq!(super::unhexdump(&text, None), Ok(BYTES[12..20].to_vec()));
+	}
+
+	#[test]
+	fn rel_base() {
+		let dump = hexdump(&BYTES[12..40], 12).rel_base(0x10).annotate(&[(4..6, "field")]);
+		assert_eq!(format!("{}", dump).lines().collect::<Vec<_>>(), [
+			"0000000C: -0004                                       28 E9 66 FE  |            (.f.|",
+			"00000010: +0000  45 72 72 6F 72 20 63 6F  64 65 20 00 00 00 00 00  |Error code .....|",
+			"                 ^^^^^ field",
+			"00000020: +0010  00 11 22 33 44 55 66 77                           |..\"3DUfw        |",
+		]);
+		assert_eq!(dump.hit_test(1, 17), Some(16));
+		// The relative offsets of every row are as wide as the widest
+		let bytes = vec![0x55u8; 0x10010];
+		let dump = hexdump(&bytes, 0).rel_base(0);
+		let first = dump.clone().into_lines().next().unwrap();
+		assert!(first.starts_with("00000000: +00000  55 "));
+		let mut buf = [0u8; 96];
+		let len = super::format_row_into(&mut buf, 0, &bytes[..16], dump.config()).unwrap();
+		assert_eq!(::std::str::from_utf8(&buf[..len]), Ok(&first[..]));
+	}
+
+	#[test]
+	fn hexgrid() {
+		let a = &BYTES[..16];
+		let b = &BYTES[16..32];
+		let c = &BYTES[6..10];
+		assert_eq!(format!("{}", super::hexgrid(&[a, b, c], 8)).lines().collect::<Vec<_>>(), [
+			"00000008:                           48 83 EC 28 E8 1B 03 00                           45 72 72 6F 72 20 63 6F                           03 00 00 48",
+			"00000010:  00 48 83 C4 28 E9 66 FE                           64 65 20 00 00 00 00 00",
+		]);
+		assert_eq!(format!("{}", super::hexgrid(&[], 0)), "");
+	}
+
+	#[test]
+	fn little_endian() {
+		let dump = hexdump(&BYTES[2..24], 2).group(4).little_endian(true).highlights(&[1..3, 20..21]);
+		let rows = format!("{}", dump);
+		assert_eq!(rows.lines().collect::<Vec<_>>(), [
+			"00000002:  \x1b[7m28\x1b[0mEC     00031B\x1b[7mE8\x1b[0m C4834800 FE66E928  |  .\x1b[7m(.\x1b[0m....H..(.f.|",
+			"00000010:  6F727245 6F\x1b[7m63\x1b[0m2072                    |Error \x1b[7mc\x1b[0mo        |",
+		]);
+		// The gutter follows the swapped words, a partial word keeps its glyphs at the columns of its hex
+		let dump = dump.swap_gutter(true);
+		let swapped = format!("{}", dump);
+		let gutters: Vec<&str> = swapped.lines().map(|line| &line[line.find('|').unwrap()..]).collect();
+		assert_eq!(gutters, [
+			"|\x1b[7m(\x1b[0m.  ...\x1b[7m.\x1b[0m..H..f.(|",
+			"|orrEo\x1b[7mc\x1b[0m r        |",
+		]);
+		let mut buf = [0u8; 80];
+		let len = super::format_row_into(&mut buf, 0x10, &BYTES[16..24], dump.config()).unwrap();
+		assert_eq!(&buf[..len], &b"00000010:  6F727245 6F632072                    |orrEoc r        |"[..]);
+		// The words are mirrored in the hex and the swapped gutter
+		let dump = hexdump(&BYTES[..24], 0).group(4).little_endian(true);
+		// 00000000:  28EC8348 00031BE8 C4834800 FE66E928  |H..(.....H..(.f.|
+		assert_eq!(dump.hit_test(0, 11), Some(3));
+		assert_eq!(dump.hit_test(0, 17), Some(0));
+		assert_eq!(dump.hit_test(0, 49), Some(0));
+		assert_eq!(dump.hit_test(0, 52), Some(3));
+		// 00000010:  6F727245 6F632072                    |Error co        |
+		assert_eq!(dump.hit_test(1, 11), Some(19));
+		assert_eq!(dump.hit_test(1, 20), Some(23));
+		let dump = dump.swap_gutter(true);
+		assert_eq!(dump.hit_test(0, 49), Some(3));
+		assert_eq!(dump.hit_test(0, 52), Some(0));
+		assert_eq!(dump.hit_test(1, 49), Some(19));
+	}
+
+	#[test]
+	fn expanded() {
+		let dump = hexdump(b"Hi\x00", 6).expanded(true);
+		assert_eq!(format!("{}", dump), "00000006:                                      48(H) 69(i)  00(.)\n");
+		assert_eq!(dump.hit_test(0, 53), Some(7));
+		let mut buf = [0u8; 128];
+		let len = super::format_row_into(&mut buf, 6, b"Hi\x00", dump.config()).unwrap();
+		assert_eq!(&buf[..len], format!("{}", dump).trim_end().as_bytes());
+	}
+
+	#[test]
+	fn prefix_fn() {
+		let dump = hexdump(&BYTES[12..40], 12).prefix_fn(|f, index, address| write!(f, "#{} {:X} ", index, address)).annotate(&[(4..6, "field")]);
+		assert_eq!(format!("{}", dump).lines().collect::<Vec<_>>(), [
+			"#0 C 0000000C:                                       28 E9 66 FE  |            (.f.|",
+			"#1 10 00000010:  45 72 72 6F 72 20 63 6F  64 65 20 00 00 00 00 00  |Error code .....|",
+			"                 ^^^^^ field",
+			"#2 20 00000020:  00 11 22 33 44 55 66 77                           |..\"3DUfw        |",
+		]);
+		let lines: Vec<String> = dump.clone().squeeze(true).into_lines().collect();
+		assert_eq!(lines, format!("{}", dump.clone().squeeze(true)).lines().collect::<Vec<_>>());
+		// The prefixes differ in width between the rows
+		assert_eq!(dump.hit_test(0, 5 + 48), Some(12));
+		assert_eq!(dump.hit_test(1, 5 + 11), None);
+		assert_eq!(dump.hit_test(1, 6 + 11), Some(16));
+		assert_eq!(dump.hit_test(1, 6 + 62), Some(16));
+	}
+
+	#[test]
+	fn line_numbers() {
+		let dump = hexdump(&BYTES[12..40], 12).line_numbers(true).annotate(&[(4..6, "field")]);
+		assert_eq!(format!("{}", dump.clone().prefix_fn(|f, _, _| f.write_str("| "))).lines().collect::<Vec<_>>(), [
+			"     1 | 0000000C:                                       28 E9 66 FE  |            (.f.|",
+			"     2 | 00000010:  45 72 72 6F 72 20 63 6F  64 65 20 00 00 00 00 00  |Error code .....|",
+			"                    ^^^^^ field",
+			"     3 | 00000020:  00 11 22 33 44 55 66 77                           |..\"3DUfw        |",
+		]);
+		assert!(format!("{}", dump).starts_with("     1 0000000C: "));
+		assert_eq!(dump.hit_test(0, 17), None);
+		assert_eq!(dump.hit_test(0, 18 + 37), Some(12));
+		assert_eq!(dump.hit_test(1, 18), Some(16));
+		assert_eq!(dump.hit_test(1, 68), None);
+		assert_eq!(dump.hit_test(1, 69), Some(16));
+	}
+
+	#[test]
+	fn segments() {
+		let segs = [(0x1000..0x1010, ".text"), (0x1010..0x1020, ".data")];
+		let dump = hexdump(&BYTES[..40], 0).display_offset(0xFFC).segments(&segs).annotate(&[(24..26, "x")]);
+		assert_eq!(format!("{}", dump).lines().collect::<Vec<_>>(), [
+			"00000FFC:                                         48 83 EC 28  |            H..(|",
+			".text+0x00:  E8 1B 03 00 00 48 83 C4  28 E9 66 FE 45 72 72 6F  |.....H..(.f.Erro|",
+			".data+0x00:  72 20 63 6F 64 65 20 00  00 00 00 00 00 11 22 33  |r code .......\"3|",
+			"                         ^^^^^ x",
+			"00001020:    44 55 66 77                                       |DUfw            |",
+		]);
+		assert_eq!(dump.hit_test(2, 25), Some(0x1014));
+		let dump = hexdump(&BYTES[..8], 0).display_offset(0x1014).segments(&segs);
+		assert!(format!("{}", dump).starts_with(".data+0x04:"));
+		// The segment rows cannot be reversed
+		assert_eq!(super::unhexdump(&format!("{}", dump), None), Err(super::ParseError::InvalidRow { line: 1 }));
+	}
+
+	#[test]
+	fn tool_presets() {
+		let bytes = b"Hello, hexdump!\x00\x01\x80~ end";
+		// Output of `xxd`
+		assert_eq!(format!("{}", hexdump(bytes, 0).with_config(Config::xxd_default())),
+			"00000000: 4865 6c6c 6f2c 2068 6578 6475 6d70 2100  Hello, hexdump!.\n\
+			 00000010: 0180 7e20 656e 64                        ..~ end\n");
+		// Output of `hexdump -C`
+		let mut repeated = bytes.to_vec();
+		repeated.splice(16..16, bytes[..16].repeat(2));
+		assert_eq!(format!("{}", hexdump(&repeated, 0).with_config(Config::bsd_default())),
+			"00000000  48 65 6c 6c 6f 2c 20 68  65 78 64 75 6d 70 21 00  |Hello, hexdump!.|\n\
+			 *\n\
+			 00000030  01 80 7e 20 65 6e 64                              |..~ end|\n\
+			 00000037\n");
+		// Repeated rows at the end are squeezed as well
+		let dump = hexdump(&repeated[..0x30], 0).with_config(Config::bsd_default());
+		assert_eq!(format!("{}", dump),
+			"00000000  48 65 6c 6c 6f 2c 20 68  65 78 64 75 6d 70 21 00  |Hello, hexdump!.|\n\
+			 *\n\
+			 00000030\n");
+		assert_eq!(dump.into_lines().collect::<Vec<_>>(), ["00000000  48 65 6c 6c 6f 2c 20 68  65 78 64 75 6d 70 21 00  |Hello, hexdump!.|", "*", "00000030"]);
+		let dump = hexdump(bytes, 0).with_config(Config::xxd_default());
+		assert_eq!(dump.hit_test(1, 17), Some(0x13));
+		assert_eq!(dump.hit_test(1, 51 + 3), Some(0x13));
+		let mut buf = [0u8; 80];
+		let len = super::format_row_into(&mut buf, 0x10, &bytes[16..], dump.config()).unwrap();
+		assert_eq!(&buf[..len], &b"00000010: 0180 7e20 656e 64                        ..~ end"[..]);
+	}
+
+	#[test]
+	fn framed() {
+		let dump = format!("{}", hexdump(&BYTES[12..20], 12).framed(true));
+		assert_eq!(dump.lines().collect::<Vec<_>>(), [
+			super::header(),
+			ruler(),
+			"0000000C:                                       28 E9 66 FE  |            (.f.|",
+			"00000010:  45 72 72 6F                                       |Erro            |",
+			ruler(),
+		]);
+		assert_eq!(super::header().len(), ruler().len());
+	}
+
+	#[test]
+	fn radix() {
+		let dump = format!("{}", hexdump(&BYTES[5..28], 5).radix(Radix::Decimal));
+		assert_eq!(dump.lines().collect::<Vec<_>>(), [
+			"00000005:                       27   3   0    0  72 131 196  40 233 102 254  |     ....H..(.f.|",
+			"00000010:   69 114 114 111 114  32  99 111  100 101  32   0                  |Error code .    |",
+		]);
+		let dump = format!("{}", hexdump(&BYTES[12..20], 12).radix(Radix::Binary).dim_zeros(Some('_')));
+		assert_eq!(dump.lines().collect::<Vec<_>>(), [
+			"0000000C:                                                                                                               00101000 11101001 01100110 11111110  |            (.f.|",
+			"00000010:  01000101 01110010 01110010 01101111                                                                                                               |Erro            |",
+		]);
+		assert_eq!(format!("{}", hexdump(b"\x00\x08", 0).radix(Radix::Octal).dim_zeros(Some('_'))),
+			"00000000:  ___ 010                                                           |..              |\n");
+	}
+
+	#[test]
+	fn dim_zeros() {
+		assert_eq!(format!("{}", hexdump(b"\x00\x00\x00\x2A\x00\x00\x00\x00\x10\x00", 0).dim_zeros(Some(' '))),
+			"00000000:           2A              10                       |...*......      |\n");
+		assert_eq!(format!("{}", hexdump(b"\x00\x01", 0).dim_zeros(Some('_'))),
+			"00000000:  __ 01                                             |..              |\n");
+		// The expanded glyph follows the placeholder like it follows the digits
+		let dump = hexdump(b"\x00A", 0).expanded(true).dim_zeros(Some('_'));
+		let row = format!("{}", dump);
+		assert_eq!(row, "00000000:  __(.) 41(A)\n");
+		let mut buf = [0u8; 128];
+		let len = super::format_row_into(&mut buf, 0, b"\x00A", dump.config()).unwrap();
+		assert_eq!(::std::str::from_utf8(&buf[..len]).unwrap(), row.trim_end_matches('\n'));
+	}
+
+	#[test]
+	fn mask() {
+		// Only the bytes deviating from the template are shown
+		let template = b"\x7FELF\x02\x01\x01\x00";
+		let header = b"\x7FELF\x01\x01\x01\x00\x00\x00\x00\x00\x00\x00\x00\x00\x02\x00";
+		assert_eq!(format!("{}", hexdump(header, 0).mask(template, '~')),
+			"00000000:  ~~ ~~ ~~ ~~ 01 ~~ ~~ ~~  00 00 00 00 00 00 00 00  |~~~~.~~~........|\n\
+			 00000010:  02 00                                             |..              |\n");
+		assert_eq!(format!("{}", hexdump(&header[2..6], 2).mask(&template[2..], '_').expanded(true)),
+			"00000002:              __(_) __(_) 01(.) __(_)\n");
+	}
+
+	#[test]
+	fn colors() {
+		let dump = |theme| format!("{}", hexdump(b"\x00A", 0).colors(theme));
+		assert_eq!(dump(ColorTheme::default()),
+			"00000000:  \x1b[38;2;128;128;128m00\x1b[39m \x1b[38;2;13;188;121m41\x1b[39m                                             |.A              |\n");
+		assert!(dump(ColorTheme::SOLARIZED).starts_with("00000000:  \x1b[38;2;88;110;117m00\x1b[39m "));
+		assert_eq!(dump(ColorTheme::MONO), format!("{}", hexdump(b"\x00A", 0)));
+		// Colored dumps can still be reversed
+		assert_eq!(super::unhexdump(&dump(ColorTheme::default()), None), Ok(b"\x00A".to_vec()));
+	}
+
+	#[test]
+	fn entropy_shading() {
+		let mut bytes = [0u8; 0x40];
+		for (i, byte) in bytes[0x20..].iter_mut().enumerate() {
+			*byte = (i as u8).wrapping_mul(0x9D) ^ 0x5A;
+		}
+		let dump = format!("{}", hexdump(&bytes, 0).entropy_shading(true));
+		let lines: Vec<&str> = dump.lines().collect();
+		assert!(lines[0].starts_with("00000000:  \x1b[48;2;32;32;192m00\x1b[49m \x1b[48;2;32;32;192m00\x1b[49m "));
+		assert!(lines[3].starts_with("00000030:  \x1b[48;2;192;32;32m8A\x1b[49m "));
+		// Shaded dumps can still be reversed
+		assert_eq!(super::unhexdump(&dump, None), Ok(bytes.to_vec()));
+	}
+
+	#[test]
+	fn gutter_margin() {
+		assert_eq!(format!("{}", hexdump(&BYTES[12..20], 12).gutter_margin(4)),
+			"0000000C:                                       28 E9 66 FE    |            (.f.|\n\
+			 00000010:  45 72 72 6F                                         |Erro            |\n");
+		assert_eq!(format!("{}", hexdump(&BYTES[32..48], 32).gutter_margin(0)),
+			"00000020:  00 11 22 33 44 55 66 77  88 99 AA BB CC DD EE FF|..\"3DUfw........|\n");
+		let dump = hexdump(&BYTES[32..48], 32).gutter_margin(4);
+		assert_eq!(dump.hit_test(0, 62), None);
+		assert_eq!(dump.hit_test(0, 64), Some(32));
+	}
+
+	#[test]
+	fn columns() {
+		let dump = hexdump(&BYTES[12..20], 12).annotate(&[(4..6, "x")]);
+		assert_eq!(format!("{}", dump.clone().columns(&[Column::Ascii, Column::Hex]).unwrap()).lines().collect::<Vec<_>>(), [
+			"|            (.f.|                                      28 E9 66 FE",
+			"|Erro            | 45 72 72 6F                                     ",
+		]);
+		// The annotations follow the hex bytes when they come first
+		assert_eq!(format!("{}", dump.columns(&[Column::Hex, Column::Ascii]).unwrap()).lines().collect::<Vec<_>>(), [
+			"                                      28 E9 66 FE  |            (.f.|",
+			" 45 72 72 6F                                       |Erro            |",
+			" ^^^^^ x",
+		]);
+		// The default order is the same as without columns
+		let order = [Column::Address, Column::Hex, Column::Ascii, Column::Notes];
+		let dump = hexdump(&BYTES[5..28], 5).nonzero_count(true).annotate(&[(4..6, "x")]);
+		assert_eq!(format!("{}", dump.clone().columns(&order).unwrap()), format!("{}", dump));
+		assert_eq!(dump.clone().columns(&order).unwrap().hit_test(1, 62), Some(16));
+		// Only hit the columns which are where the default order puts them
+		let dump = hexdump(&BYTES[12..20], 12);
+		assert_eq!(dump.clone().columns(&[Column::Ascii, Column::Hex]).unwrap().hit_test(1, 1), None);
+		let notes = dump.columns(&[Column::Address, Column::Hex, Column::Notes]).unwrap();
+		assert_eq!(notes.hit_test(1, 11), Some(16));
+		assert_eq!(notes.hit_test(1, 62), None);
+		assert_eq!(hexdump(BYTES, 0).columns(&[Column::Address, Column::Notes]).err(), Some(BuildError::NoContentColumn));
+	}
+
+	#[test]
+	fn no_trailing_ws() {
+		let dump = hexdump(&BYTES[5..28], 5).gutter_pipes(false).annotate(&[(4..6, "")]);
+		let padded = format!("{}", dump);
+		assert!(padded.lines().any(|line| line.ends_with(' ')));
+		let dump = dump.no_trailing_ws(true);
+		let trimmed = format!("{}", dump);
+		assert!(!trimmed.lines().any(|line| line.ends_with(' ')));
+		assert_eq!(trimmed.lines().collect::<Vec<_>>(), padded.lines().map(str::trim_end).collect::<Vec<_>>());
+		assert_eq!(trimmed.lines().nth(2), Some("00000010:  45 72 72 6F 72 20 63 6F  64 65 20 00               Error code ."));
+		assert_eq!(dump.clone().into_lines().collect::<Vec<_>>(), trimmed.lines().collect::<Vec<_>>());
+		let mut buf = [0u8; 96];
+		let len = super::format_row_into(&mut buf, 0x10, &BYTES[16..28], dump.config()).unwrap();
+		assert_eq!(&buf[..len], trimmed.lines().nth(2).unwrap().as_bytes());
+	}
+
+	#[test]
+	fn golden() {
+		let golden = hexdump(&BYTES[5..48], 5).golden();
+		assert_eq!(golden.lines().next(), Some("00000005:                 1b 03 00  00 48 83 c4 28 e9 66 fe  |     ....H..(.f.|"));
+		let configured = hexdump(&BYTES[5..48], 5)
+			.colors(ColorTheme::default())
+			.squeeze(true)
+			.framed(true)
+			.group(4)
+			.charset(Charset::Caret)
+			.highlights(&[0..4, 9..10])
+			.annotate(&[(4..6, "x")])
+			.nonzero_count(true)
+			.columns(&[Column::Ascii]).unwrap();
+		assert_eq!(configured.golden(), golden);
+		assert!(!golden.contains('\x1b') && !golden.contains('\r'));
+		assert_eq!(super::unhexdump(&golden, None), Ok(BYTES[5..48].to_vec()));
+	}
+
+	#[test]
+	fn schema() {
+		let dump = hexdump(&BYTES[..24], 0).schema("magic:4, version:6, payload:*").unwrap();
+		let s = format!("{}", dump);
+		assert_eq!(s, "\
+			00000000:  48 83 EC 28·E8 1B 03 00  00 48·83 C4 28 E9 66 FE  |H..(.....H..(.f.|\n\
+			\x20          ^^^^^^^^^^^ magic\n\
+			\x20                      ^^^^^^^^^^^^^^^^^^ version\n\
+			\x20                                         ^^^^^^^^^^^^^^^^^ payload\n\
+			00000010:  45 72 72 6F 72 20 63 6F                           |Error co        |\n\
+			\x20          ^^^^^^^^^^^^^^^^^^^^^^^\n");
+		let mut buf = [0u8; 96];
+		let len = super::format_row_into(&mut buf, 0, &BYTES[..16], dump.config()).unwrap();
+		assert_eq!(::std::str::from_utf8(&buf[..len]), Ok(s.lines().next().unwrap()));
+		// The fields start at the offset of the dump
+		let dump = hexdump(&BYTES[3..24], 3).schema("magic:4, version:16, payload:*").unwrap();
+		let len = super::format_row_into(&mut buf, 0x10, &BYTES[16..24], dump.config()).unwrap();
+		assert_eq!(::std::str::from_utf8(&buf[..len]), Ok("00000010:  45 72 72 6F 72 20 63·6F                           |Error co        |"));
+		assert!(format!("{}", dump).contains("00000010:  45 72 72 6F 72 20 63·6F                           |Error co        |\n"));
+		assert_eq!(super::unhexdump(&s, None), Ok(BYTES[..24].to_vec()));
+
+		assert_eq!(hexdump(BYTES, 0).schema("magic:4,payload:*,crc:4").err(), Some(BuildError::InvalidSchema { field: 2 }));
+		assert_eq!(hexdump(BYTES, 0).schema("magic:4,version").err(), Some(BuildError::InvalidSchema { field: 1 }));
+		assert_eq!(hexdump(BYTES, 0).schema(":4").err(), Some(BuildError::InvalidSchema { field: 0 }));
+		assert_eq!(hexdump(BYTES, 0).schema("magic:0").err(), Some(BuildError::InvalidSchema { field: 0 }));
+		// Fields past the end of the address space
+		let huge = format!("magic:4,a:{},b:{}", usize::MAX / 2, usize::MAX / 2);
+		assert_eq!(hexdump(BYTES, 0).schema(&huge).err(), Some(BuildError::InvalidSchema { field: 2 }));
+	}
+
+	#[test]
+	fn gutter_pipes() {
+		let piped = format!("{}", hexdump(&BYTES[5..28], 5));
+		let dump = format!("{}", hexdump(&BYTES[5..28], 5).gutter_pipes(false));
+		assert!(!dump.contains('|'));
+		assert_eq!(dump, piped.replace('|', " "));
+		assert_eq!(dump.lines().next().unwrap(), "00000005:                 1B 03 00  00 48 83 C4 28 E9 66 FE        ....H..(.f. ");
+	}
+
+	#[test]
+	fn compact_gutter() {
+		assert_eq!(format!("{}", hexdump(b"hello", 0).compact_gutter(true)),
+			"00000000:  68 65 6C 6C 6F                                    |hello|\n");
+		assert_eq!(format!("{}", hexdump(&BYTES[5..28], 5).compact_gutter(true)),
+			format!("{}", hexdump(&BYTES[5..28], 5)));
+		// Single rows have the gutter of the dump
+		let dump = hexdump(&BYTES[..20], 0).compact_gutter(true);
+		let mut buf = [0u8; 80];
+		for (row, line) in dump.rows().zip(format!("{}", dump).lines()) {
+			let len = super::format_row_into(&mut buf, row.address(), row.bytes(), dump.config()).unwrap();
+			assert_eq!(::std::str::from_utf8(&buf[..len]), Ok(line));
+		}
+		let dump = hexdump(&BYTES[..6], 4).compact_gutter(true);
+		let len = super::format_row_into(&mut buf, 4, &BYTES[..6], dump.config()).unwrap();
+		assert_eq!(::std::str::from_utf8(&buf[..len]), Ok(format!("{}", dump).trim_end_matches('\n')));
+	}
+
+	#[test]
+	fn charsets() {
+		let bytes = b"\x00caf\xC3\xA9\xE9\x7F\xC8\x85\x93\x93\x96";
+		let gutter = |charset| {
+			let dump = format!("{}", hexdump(bytes, 0).charset(charset));
+			dump[61..dump.len() - 1].to_owned()
+		};
+		assert_eq!(gutter(Charset::Ascii), "|.caf...\u{7F}.....   |");
+		assert_eq!(gutter(Charset::Latin1), "|.cafÃ©é\u{7F}È....   |");
+		assert_eq!(gutter(Charset::Utf8), "|.café .\u{7F}ȅ ...   |");
+		assert_eq!(gutter(Charset::Ebcdic), "|.Ä/ÃCzZ\"Hello   |");
+		assert_eq!(gutter(Charset::Caret), "|^@caf...^?.....   |");
+		assert_eq!(gutter(Charset::MixedHex), "|<00>caf<C3><A9><E9><7F><C8><85><93><93><96>|");
+
+		let dump = hexdump(b"a\x00b<", 14).charset(Charset::MixedHex);
+		assert_eq!(format!("{}", dump),
+			"0000000E:                                             61 00  |a<00>|\n\
+			 00000010:  62 3C                                             |b<3C>|\n");
+		assert_eq!(dump.hit_test(0, 62), Some(14));
+		assert_eq!(dump.hit_test(0, 66), Some(15));
+		assert_eq!(dump.hit_test(1, 63), Some(17));
+
+		let utf16 = |bytes: &[u8], charset| format!("{}", hexdump(bytes, 0).charset(charset))[61..].to_owned();
+		assert_eq!(utf16(b"H\x00i\x00", Charset::Utf16Le), "|H i             |\n");
+		assert_eq!(utf16(b"\x00H\x00i\x00", Charset::Utf16Be), "|H i .           |\n");
+		assert_eq!(utf16(b"\x3D\xD8\x00\x00\xE9\x00", Charset::Utf16Le), "|. . é           |\n");
+	}
+
+	#[test]
+	fn highlights() {
+		let ranges = [1..3, 2..4, 14..18];
+		let dump = hexdump(&BYTES[..20], 0).highlights(&ranges);
+		let expected = "00000000:  48 \x1b[7m83 EC 28\x1b[0m E8 1B 03 00  00 48 83 C4 28 E9 \x1b[7m66 FE\x1b[0m  |H\x1b[7m..(\x1b[0m.....H..(.\x1b[7mf.\x1b[0m|\n\
+		                00000010:  \x1b[7m45 72\x1b[0m 72 6F                                       |\x1b[7mEr\x1b[0mro            |\n";
+		assert_eq!(format!("{}", dump), expected);
+		// The same ranges can be reused for the next dump
+		assert_eq!(format!("{}", hexdump(&BYTES[..20], 0).highlights(&ranges)), expected);
+		assert_eq!(dump.effective_highlights(), [1..4, 14..18]);
+		assert_eq!(hexdump(BYTES, 0).highlights(&[0..4, 3..8, 10..12]).effective_highlights(), [0..8, 10..12]);
+		assert_eq!(hexdump(BYTES, 0).highlights(&[12..14, 9..9, 4..8, 0..4]).effective_highlights(), [0..8, 12..14]);
+	}
+
+	#[test]
+	fn find_pattern() {
+		let dump = hexdump(&BYTES[..40], 0).find_pattern("48 ?? EC 28").unwrap();
+		assert_eq!(format!("{}", dump).lines().next(),
+			Some("00000000:  \x1b[7m48 83 EC 28\x1b[0m E8 1B 03 00  00 48 83 C4 28 E9 66 FE  |\x1b[7mH..(\x1b[0m.....H..(.f.|"));
+		// Every match is highlighted, merged with the other highlights
+		let dump = hexdump(&BYTES[..40], 0).highlights(&[20..22, 30..31]).find_pattern("48 83 ?? 28").unwrap();
+		assert_eq!(dump.effective_highlights(), [0..4, 9..13, 20..22, 30..31]);
+		assert_eq!(hexdump(BYTES, 0).find_pattern("48 ?? GG").err(), Some(BuildError::InvalidPattern { token: 2 }));
+		assert_eq!(hexdump(BYTES, 0).find_pattern("48 8").err(), Some(BuildError::InvalidPattern { token: 1 }));
+		assert_eq!(hexdump(BYTES, 0).find_pattern(" ").err(), Some(BuildError::EmptyPattern));
+	}
+
+	#[test]
+	fn rows() {
+		let dump = hexdump(&BYTES[..20], 5);
+		let mut rows = dump.rows();
+		let row = rows.next().unwrap();
+		assert_eq!(row.address(), 5);
+		assert_eq!(row.bytes(), &BYTES[..11]);
+		assert_eq!(row.skip(), 5);
+		let (mut hex, mut ascii) = (String::new(), String::new());
+		row.write_hex(&mut hex).unwrap();
+		row.write_ascii(&mut ascii, Charset::Ascii).unwrap();
+		assert_eq!(hex, "               48 83 EC  28 E8 1B 03 00 00 48 83");
+		assert_eq!(ascii, "     H..(.....H.");
+		assert_eq!(rows.next().map(|row| (row.address(), row.skip())), Some((16, 0)));
+		assert!(rows.next().is_none());
+		let mut json = String::new();
+		hexdump(b"\"hi\" \\o/", 0x10).rows().next().unwrap().write_json(&mut json, Charset::Ascii).unwrap();
+		assert_eq!(json, r#"{"offset":16,"hex":"22 68 69 22 20 5C 6F 2F","ascii":"\"hi\" \\o/"}"#);
+	}
+
+	#[test]
+	fn cells() {
+		let cells: Vec<_> = hexdump(&BYTES[14..18], 14).cells().collect();
+		assert_eq!(cells, [(14, 0x66, 'f'), (15, 0xFE, '.'), (16, 0x45, 'E'), (17, 0x72, 'r')]);
+		// The glyphs follow the charset
+		let cells: Vec<_> = hexdump("é\x01".as_bytes(), 0).charset(Charset::Utf8).cells().collect();
+		assert_eq!(cells, [(0, 0xC3, 'é'), (1, 0xA9, ' '), (2, 0x01, '.')]);
+		let cells: Vec<_> = hexdump(b"a\x01", 0).charset(Charset::Caret).cells().map(|(_, _, glyph)| glyph).collect();
+		assert_eq!(cells, ['a', '.']);
+	}
+
+	#[test]
+	fn into_lines() {
+		struct Viewer<'a> {
+			lines: IntoLines<'a>,
+		}
+		let dump = hexdump(&BYTES[5..48], 5).framed(true).squeeze(true).annotate(&[(2..4, "field")]);
+		let expected = format!("{}", dump);
+		let mut viewer = Viewer { lines: dump.into_lines() };
+		assert_eq!(viewer.lines.next().as_deref(), Some(super::header()));
+		let rest: Vec<String> = viewer.lines.collect();
+		assert_eq!(rest, expected.lines().skip(1).collect::<Vec<_>>());
+		assert_eq!(hexdump(&[], 0).into_lines().count(), 0);
+	}
+
+	#[test]
+	fn hit_test() {
+		let dump = hexdump(&BYTES[5..28], 5);
+		// 00000005:                 1B 03 00  00 48 83 C4 28 E9 66 FE  |     ....H..(.f.|
+		assert_eq!(dump.hit_test(0, 11), None);
+		assert_eq!(dump.hit_test(0, 26), Some(5));
+		assert_eq!(dump.hit_test(0, 27), Some(5));
+		assert_eq!(dump.hit_test(0, 28), None);
+		assert_eq!(dump.hit_test(0, 36), Some(8));
+		assert_eq!(dump.hit_test(0, 35), None);
+		assert_eq!(dump.hit_test(0, 66), None);
+		assert_eq!(dump.hit_test(0, 67), Some(5));
+		assert_eq!(dump.hit_test(0, 77), Some(15));
+		assert_eq!(dump.hit_test(0, 78), None);
+		// 00000010:  45 72 72 6F 72 20 63 6F  64 65 20 00              |Error code .    |
+		assert_eq!(dump.hit_test(1, 4), None);
+		assert_eq!(dump.hit_test(1, 11), Some(16));
+		assert_eq!(dump.hit_test(1, 62), Some(16));
+		assert_eq!(dump.hit_test(1, 73), Some(27));
+		assert_eq!(dump.hit_test(1, 74), None);
+		assert_eq!(dump.hit_test(2, 11), None);
+		// Glyphs wider than a column
+		let dump = hexdump(b"\x00a", 0).charset(Charset::Caret);
+		assert_eq!(dump.hit_test(0, 63), Some(0));
+		assert_eq!(dump.hit_test(0, 64), Some(1));
+		// Addresses past 4 GiB are not truncated
+		let dump = hexdump(b"hexdump", 0).display_offset(0x1_0000_0010);
+		assert_eq!(dump.hit_test(0, 15), Some(0x1_0000_0011));
+	}
+
+	#[test]
+	fn rle_note() {
+		assert_eq!(format!("{}", hexdump(b"\x01\x02\x00\x00\x00\x00\x03\x04\x04\x05", 0x10).rle_note(3)),
+			"00000010:  01 02 00 00 00 00 03 04  04 05                    |..........      | (00 x4 @0x12)\n");
+	}
+
+	#[test]
+	fn nonzero_count() {
+		let dump = hexdump(b"\x01\x02\x00\x00\x00\x00\x03\x04\x04\x05\x00\x00\x00\x00\x00\x00\x00", 0x10).nonzero_count(true);
+		assert_eq!(format!("{}", dump.clone().rle_note(4)),
+			"00000010:  01 02 00 00 00 00 03 04  04 05 00 00 00 00 00 00  |................| [nz=6] (00 x4 @0x12) (00 x6 @0x1A)\n\
+			 00000020:  00                                                |.               | [nz=0]\n");
+		let mut buf = [0u8; 96];
+		let len = super::format_row_into(&mut buf, 0x10, b"\x00\x01\x02\x03\x04\x05\x06\x07", dump.config()).unwrap();
+		assert!(buf[..len].ends_with(b"| [nz=7]"));
+	}
+
+	#[test]
+	fn nibble_grid() {
+		let tiles = [0x0F, 0xF0, 0x0F, 0xF0, 0x3C, 0xC3, 0x3C, 0xC3, 0x0F, 0xF0, 0x0F, 0xF0, 0x3C, 0xC3, 0x3C, 0xC3];
+		let mut bytes = tiles.to_vec();
+		bytes.extend_from_slice(&tiles[..8]);
+		let dump = format!("{}", hexdump(&bytes, 0).nibble_grid(true));
+		let lines: Vec<&str> = dump.lines().collect();
+		assert_eq!(lines, [
+			"00000000:  0FF00FF03CC33CC30FF00FF03CC33CC3",
+			"00000010:  0FF00FF03CC33CC3                ",
+		]);
+		for line in &lines {
+			assert_eq!(line.len() - "00000000:  ".len(), 16 * 2);
+		}
+		// The nibble grid ignores the column order
+		let grid = hexdump(&bytes, 0).nibble_grid(true);
+		for order in &[&[Column::Ascii][..], &[Column::Hex, Column::Ascii]] {
+			assert_eq!(format!("{}", grid.clone().columns(order).unwrap()), dump);
+		}
+		let dump = grid;
+		let mut buf = [0u8; 64];
+		for (row, line) in dump.rows().zip(lines) {
+			let len = super::format_row_into(&mut buf, row.address(), row.bytes(), dump.config()).unwrap();
+			assert_eq!(&buf[..len], line.as_bytes());
+		}
+		let dump = hexdump(&bytes[5..], 5).nibble_grid(true);
+		assert_eq!(dump.hit_test(0, 11 + 9), None);
+		assert_eq!(dump.hit_test(0, 11 + 10), Some(5));
+		assert_eq!(dump.hit_test(0, 11 + 31), Some(15));
+		assert_eq!(dump.hit_test(0, 11 + 32), None);
+		assert_eq!(dump.hit_test(1, 11 + 15), Some(23));
+		assert_eq!(dump.hit_test(1, 11 + 16), None);
+	}
+
+	#[test]
+	fn braille_overview() {
+		let mut bytes = [0u8; 0x30];
+		bytes[0x10..0x20].copy_from_slice(b"hexdump overview");
+		bytes[0x21] = 1;
+		bytes[0x2F] = 1;
+		let dump = hexdump(&bytes, 0).braille_overview(true);
+		let glyphs: Vec<char> = format!("{}", dump).lines().map(|line| line.chars().last().unwrap()).collect();
+		assert_eq!(glyphs, ['\u{2800}', '\u{28FF}', '\u{2881}']);
+		let mut buf = [0u8; 96];
+		let len = super::format_row_into(&mut buf, 0x10, &bytes[0x10..0x20], dump.config()).unwrap();
+		assert!(buf[..len].ends_with("| \u{28FF}".as_bytes()));
+	}
+
+	#[test]
+	fn histogram() {
+		let mut bytes = [0u8; 0x20];
+		bytes[4..8].copy_from_slice(b"\xFF\xFF\xFFA");
+		assert_eq!(format!("{}", hexdump(&bytes, 0).squeeze(true).histogram(2)),
+			"00000000:  00 00 00 00 FF FF FF 41  00 00 00 00 00 00 00 00  |.......A........|\n\
+			 00000010:  00 00 00 00 00 00 00 00  00 00 00 00 00 00 00 00  |................|\n\
+			 ; histogram: 00 x28, FF x3\n");
+		let dump = format!("{}", hexdump(b"hexdump", 0).histogram(3));
+		assert_eq!(dump.lines().last(), Some("; histogram: 64 x1, 65 x1, 68 x1"));
+		assert_eq!(format!("{}", hexdump(b"", 0).histogram(3)), "");
+	}
+
+	#[test]
+	fn against_fill() {
+		let mut bytes = [0u8; 0x48];
+		bytes[0x13] = 0x2A;
+		bytes[0x3F] = 0xFF;
+		assert_eq!(format!("{}", hexdump(&bytes, 0).against_fill(0)),
+			"; ---- 16 bytes of 00 ----\n\
+			 00000010:  00 00 00 2A 00 00 00 00  00 00 00 00 00 00 00 00  |...*............|\n\
+			 ; ---- 16 bytes of 00 ----\n\
+			 00000030:  00 00 00 00 00 00 00 00  00 00 00 00 00 00 00 FF  |................|\n\
+			 ; ---- 8 bytes of 00 ----\n");
+	}
+
+	#[test]
+	fn filter_contains() {
+		let mut bytes = [0u8; 0x40];
+		bytes[0x1A] = 0xFF;
+		let dump = format!("{}", hexdump(&bytes[4..], 4).filter_contains(0xFF).find_pattern("FF").unwrap());
+		assert_eq!(dump,
+			"; ---- 12 bytes skipped ----\n\
+			 00000010:  00 00 00 00 00 00 00 00  00 00 \x1b[7mFF\x1b[0m 00 00 00 00 00  |..........\x1b[7m.\x1b[0m.....|\n\
+			 ; ---- 32 bytes skipped ----\n");
+		// Rows of only the fill byte are collapsed separately
+		bytes[0x3E] = 0x01;
+		assert_eq!(format!("{}", hexdump(&bytes, 0).filter_contains(0).against_fill(0)),
+			"; ---- 16 bytes of 00 ----\n\
+			 00000010:  00 00 00 00 00 00 00 00  00 00 FF 00 00 00 00 00  |................|\n\
+			 ; ---- 16 bytes of 00 ----\n\
+			 00000030:  00 00 00 00 00 00 00 00  00 00 00 00 00 00 01 00  |................|\n");
+		assert_eq!(format!("{}", hexdump(&bytes, 0).filter_contains(1).against_fill(0)),
+			"; ---- 48 bytes skipped ----\n\
+			 00000030:  00 00 00 00 00 00 00 00  00 00 00 00 00 00 01 00  |................|\n");
+	}
+
+	#[test]
+	fn record_size() {
+		let dump = format!("{}", hexdump(&BYTES[..48], 0).record_size(24));
+		assert_eq!(dump.lines().collect::<Vec<_>>(), [
+			"00000000:  48 83 EC 28 E8 1B 03 00  00 48 83 C4 28 E9 66 FE  |H..(.....H..(.f.|",
+			"00000010:  45 72 72 6F 72 20 63 6F                           |Error co        |",
+			"--- record 1 ---",
+			"00000018:                           64 65 20 00 00 00 00 00  |        de .....|",
+			"00000020:  00 11 22 33 44 55 66 77  88 99 AA BB CC DD EE FF  |..\"3DUfw........|",
+		]);
+	}
+
+	#[test]
+	fn squeeze() {
+		let mut bytes = vec![0u8; 0x1003];
+		bytes[0x1000..].copy_from_slice(b"end");
+		let expected = "00000000:  00 00 00 00 00 00 00 00  00 00 00 00 00 00 00 00  |................|\n\
+		                *\n\
+		                00001000:  65 6E 64                                          |end             |\n";
+		assert_eq!(format!("{}", hexdump(&bytes, 0).squeeze(true)), expected);
+
+		// Read in odd sized chunks to stress the reassembly of rows
+		struct Chunks<'a>(&'a [u8]);
+		impl<'a> io::Read for Chunks<'a> {
+			fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+				let n = cmp::min(cmp::min(buf.len(), 7), self.0.len());
+				buf[..n].copy_from_slice(&self.0[..n]);
+				self.0 = &self.0[n..];
+				Ok(n)
+			}
+		}
+		let mut out = Vec::new();
+		hexdump_reader(Chunks(&bytes), &mut out, 0, true).unwrap();
+		assert_eq!(String::from_utf8(out).unwrap(), expected);
+
+		// The end of the stream remains visible
+		let mut out = Vec::new();
+		hexdump_reader(&bytes[4..0x1000], &mut out, 4, true).unwrap();
+		let out = String::from_utf8(out).unwrap();
+		assert_eq!(out.matches('*').count(), 1);
+		assert_eq!(out, format!("{}", hexdump(&bytes[4..0x1000], 4).squeeze(true)));
+		assert!(out.ends_with("\n00000FF0:  00 00 00 00 00 00 00 00  00 00 00 00 00 00 00 00  |................|\n"));
+		// As do the dumps written with a config
+		let mut out = Vec::new();
+		hexdump_to(&mut out, &bytes[4..0x1000], 4, &Config::bsd_default()).unwrap();
+		let out = String::from_utf8(out).unwrap();
+		assert_eq!(out.matches('*').count(), 1);
+		assert_eq!(out, format!("{}", hexdump(&bytes[4..0x1000], 4).with_config(Config::bsd_default())));
+	}
+
+	#[test]
+	fn unhexdump() {
+		let dump = format!("{}", hexdump(&BYTES[5..48], 5).framed(true).highlights(&[3..5, 7..9]));
+		assert_eq!(super::unhexdump(&dump, None), Ok(BYTES[5..48].to_vec()));
+		let mut bytes = vec![0u8; 0x100];
+		bytes[0x80] = 1;
+		let dump = format!("{}", hexdump(&bytes, 0).squeeze(true));
+		assert_eq!(super::unhexdump(&dump, Some(0x100)), Ok(bytes));
+		assert_eq!(super::unhexdump(&dump, Some(0xFF)), Err(ParseError::TooLarge { limit: 0xFF }));
+		assert_eq!(super::unhexdump("00000000:  00 1\n", None), Err(ParseError::InvalidRow { line: 1 }));
+	}
+
+	#[test]
+	fn unhexdump_auto() {
+		let dump = format!("{}", hexdump(&BYTES[5..48], 5));
+		assert_eq!(super::unhexdump_auto(&dump, None), Ok(BYTES[5..48].to_vec()));
+		assert_eq!(super::unhexdump_auto("48 83 EC\n28\n", None), Ok(BYTES[..4].to_vec()));
+		assert_eq!(super::unhexdump_auto("# format: plain\n4883EC2\n", None), Err(ParseError::InvalidRow { line: 2 }));
+		assert_eq!(super::unhexdump_auto("4883EC28", Some(3)), Err(ParseError::TooLarge { limit: 3 }));
+		// The hint overrides the detected format
+		assert_eq!(super::unhexdump_auto("# format: hexdump\n4883EC28\n", None), Ok(Vec::new()));
+		assert_eq!(format_hint("# format: xxd-ps\n4883EC28\n"), Some("xxd-ps"));
+		assert_eq!(format_hint("4883EC28\n# format: xxd-ps\n"), None);
+		assert_eq!(DumpFormat::from_name("klingon"), None);
+	}
+
+	#[test]
+	fn unhexdump_stream() {
+		let mut bytes = BYTES[..40].repeat(4);
+		bytes.extend_from_slice(&[0xAB; 0x50]);
+		bytes.extend_from_slice(&BYTES[..40]);
+		for dump in &[format!("{}", hexdump(&bytes, 3).framed(true)), format!("{}", hexdump(&bytes, 3).squeeze(true))] {
+			assert_eq!(super::unhexdump_stream(dump.chars()).collect::<Result<Vec<u8>, _>>(), Ok(bytes.clone()));
+		}
+		let mut stream = super::unhexdump_stream("00000010:  00 11\n00000000:  22\n00000030:  33\n".chars());
+		assert_eq!(stream.by_ref().take(2).collect::<Vec<_>>(), [Ok(0x00), Ok(0x11)]);
+		assert_eq!(stream.next(), Some(Err(ParseError::InvalidRow { line: 2 })));
+		assert_eq!(stream.next(), None);
+	}
+
+	#[test]
+	fn format_row_into() {
+		let dump = hexdump(&BYTES[5..28], 5).radix(Radix::Octal).dim_zeros(Some('_')).charset(Charset::Caret);
+		let expected = format!("{}", dump);
+		let mut buf = [0u8; 256];
+		let mut rows = Vec::new();
+		for &(addr, ref range) in &[(5, 5..16), (16, 16..28)] {
+			let len = super::format_row_into(&mut buf, addr, &BYTES[range.clone()], dump.config()).unwrap();
+			rows.push(String::from_utf8(buf[..len].to_vec()).unwrap());
+		}
+		assert_eq!(expected.lines().collect::<Vec<_>>(), rows);
+
+		let dump = hexdump(&BYTES[32..48], 32);
+		let mut buf = [0u8; 79];
+		assert_eq!(super::format_row_into(&mut buf, 32, &BYTES[32..48], dump.config()), Ok(79));
+		assert_eq!(super::format_row_into(&mut buf[..78], 32, &BYTES[32..48], dump.config()), Err(BufTooSmall));
+	}
+
+	#[test]
+	fn printable_ratio() {
+		assert_eq!(super::printable_ratio(b"Display a hex dump of the specified files."), 1.0);
+		assert_eq!(super::printable_ratio(&[0x00, 0x01, 0x1F, 0x80, 0xC3, 0xFF]), 0.0);
+		assert_eq!(super::printable_ratio(&BYTES[..4]), 0.5);
+		assert_eq!(super::printable_ratio(&[]), 0.0);
+	}
+
+	#[test]
+	fn buffered() {
+		let mut out = Vec::new();
+		hexdump_buffered(&mut out, &BYTES[5..28], 5, &Config::default(), 7).unwrap();
+		assert_eq!(String::from_utf8(out).unwrap(), format!("{}", hexdump(&BYTES[5..28], 5)));
+	}
 }
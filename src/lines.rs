@@ -0,0 +1,63 @@
+use ::std::collections::VecDeque;
+use ::std::fmt::Write;
+use ::std::iter::Peekable;
+use super::{header, ruler, HexDump, Rows, RowState};
+
+/// Iterator over the formatted lines of a dump, without their newlines.
+///
+/// Created by `HexDump::into_lines`, the lines are formatted one row at a time.
+pub struct IntoLines<'a> {
+	dump: HexDump<'a>,
+	rows: Peekable<Rows<'a>>,
+	state: RowState<'a>,
+	lines: VecDeque<String>,
+	started: bool,
+	done: bool,
+}
+
+impl<'a> IntoLines<'a> {
+	pub(crate) fn new(dump: HexDump<'a>) -> IntoLines<'a> {
+		let rows = dump.rows().peekable();
+		let state = dump.row_state();
+		IntoLines { dump, rows, state, lines: VecDeque::new(), started: false, done: false }
+	}
+}
+
+impl<'a> Iterator for IntoLines<'a> {
+	type Item = String;
+	fn next(&mut self) -> Option<String> {
+		loop {
+			if let Some(line) = self.lines.pop_front() {
+				return Some(line);
+			}
+			if self.done {
+				return None;
+			}
+			// Formatting into a string cannot fail
+			let mut text = String::new();
+			if !self.started {
+				self.started = true;
+				if self.dump.cfg.framed {
+					let _ = writeln!(text, "{}", header());
+					let _ = writeln!(text, "{}", ruler());
+				}
+			}
+			// A row can produce any number of lines, none at all when it is squeezed
+			match self.rows.next() {
+				Some(row) => {
+					let last = self.rows.peek().is_none();
+					let _ = self.dump.write_row(&mut text, &mut self.state, row, last);
+				},
+				None => {
+					let _ = self.dump.finish_rows(&mut text, &mut self.state);
+					if self.dump.cfg.framed {
+						let _ = writeln!(text, "{}", ruler());
+					}
+					self.done = true;
+				},
+			}
+			let trim = self.dump.cfg.no_trailing_ws;
+			self.lines.extend(text.lines().map(|line| if trim { line.trim_end_matches(' ') } else { line }).map(String::from));
+		}
+	}
+}
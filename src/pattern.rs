@@ -0,0 +1,58 @@
+use ::std::{error, fmt};
+use ::std::ops::Range;
+
+/// Error configuring a hex dump.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum BuildError {
+	/// The pattern has no bytes.
+	EmptyPattern,
+	/// The token at this position, starting at 0, is neither a hex byte nor `??`.
+	InvalidPattern { token: usize },
+	/// The record layout has no fields.
+	EmptyLayout,
+	/// The field at this position, starting at 0, is not a known type.
+	InvalidField { field: usize },
+	/// The column order has neither the hex bytes nor the ASCII gutter.
+	NoContentColumn,
+	/// The field at this position, starting at 0, is not a `name:size` pair, has a `*` size before the last field
+	/// or ends past the largest offset.
+	InvalidSchema { field: usize },
+}
+impl fmt::Display for BuildError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			BuildError::EmptyPattern => f.write_str("empty pattern"),
+			BuildError::InvalidPattern { token } => write!(f, "invalid byte in pattern at token {}", token),
+			BuildError::EmptyLayout => f.write_str("empty record layout"),
+			BuildError::InvalidField { field } => write!(f, "unknown type in record layout at field {}", field),
+			BuildError::NoContentColumn => f.write_str("no hex or ascii column"),
+			BuildError::InvalidSchema { field } => write!(f, "invalid schema at field {}", field),
+		}
+	}
+}
+impl error::Error for BuildError {}
+
+/// Bytes to search for, `None` matches any byte.
+pub(crate) type Pattern = Vec<Option<u8>>;
+
+// Parses space separated hex bytes where `??` is a wildcard, eg. `48 ?? EC`.
+pub(crate) fn parse(pattern: &str) -> Result<Pattern, BuildError> {
+	let bytes = pattern.split_whitespace().enumerate().map(|(token, s)| match s {
+		"??" => Ok(None),
+		_ if s.len() == 2 => u8::from_str_radix(s, 16).map(Some).map_err(|_| BuildError::InvalidPattern { token }),
+		_ => Err(BuildError::InvalidPattern { token }),
+	}).collect::<Result<Pattern, _>>()?;
+	if bytes.is_empty() {
+		return Err(BuildError::EmptyPattern);
+	}
+	Ok(bytes)
+}
+
+// Finds every match of the pattern, matches may overlap.
+pub(crate) fn find(bytes: &[u8], pattern: &[Option<u8>]) -> Vec<Range<usize>> {
+	bytes.windows(pattern.len())
+		.enumerate()
+		.filter(|(_, window)| window.iter().zip(pattern).all(|(&byte, &p)| p.is_none_or(|p| p == byte)))
+		.map(|(i, _)| i..i + pattern.len())
+		.collect()
+}
@@ -0,0 +1,44 @@
+use ::std::ops::Range;
+
+/// Annotations for the fields of well-known headers.
+///
+/// The ranges are relative to the start of the dumped bytes, dump the header from its first byte.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Preset<'a> {
+	/// Ethernet II frame header: destination and source MAC and the ethertype.
+	Ethernet,
+	/// IPv4 header without options.
+	Ipv4,
+	/// User provided field table, the same as passed to `annotate`.
+	Custom(&'a [(Range<usize>, &'a str)]),
+}
+
+impl<'a> Preset<'a> {
+	/// Returns the field table of the preset.
+	pub fn fields(self) -> &'a [(Range<usize>, &'a str)] {
+		match self {
+			Preset::Ethernet => ETHERNET,
+			Preset::Ipv4 => IPV4,
+			Preset::Custom(fields) => fields,
+		}
+	}
+}
+
+static ETHERNET: &[(Range<usize>, &str)] = &[
+	(0..6, "dst mac"),
+	(6..12, "src mac"),
+	(12..14, "ethertype"),
+];
+
+static IPV4: &[(Range<usize>, &str)] = &[
+	(0..1, "version/ihl"),
+	(1..2, "dscp/ecn"),
+	(2..4, "total length"),
+	(4..6, "identification"),
+	(6..8, "flags/fragment offset"),
+	(8..9, "ttl"),
+	(9..10, "protocol"),
+	(10..12, "checksum"),
+	(12..16, "src addr"),
+	(16..20, "dst addr"),
+];
@@ -0,0 +1,50 @@
+use ::std::fmt;
+use super::{hexdump, HexDump};
+
+/// Unwrapped contents of a ring buffer, see `hexdump_ring`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RingDump {
+	bytes: Vec<u8>,
+	offset: usize,
+}
+
+/// Dumps the contents of a ring buffer in chronological order.
+///
+/// The ring is stored in the first `capacity` bytes with the oldest byte at `head`, the dump starts there
+/// and wraps around to the start of the storage. The addresses are the logical positions starting at `offset`.
+///
+/// Panics if `capacity` is larger than the bytes or `head` is past the `capacity`.
+///
+/// ```
+/// use hexdump::hexdump_ring;
+///
+/// assert_eq!(format!("{}", hexdump_ring(b"mphexdu", 2, 7, 0)),
+/// 	"00000000:  68 65 78 64 75 6D 70                              |hexdump         |\n");
+/// ```
+pub fn hexdump_ring(bytes: &[u8], head: usize, capacity: usize, offset: usize) -> RingDump {
+	let ring = &bytes[..capacity];
+	assert!(head <= capacity, "head {} is past the capacity {}", head, capacity);
+	let mut unwrapped = Vec::with_capacity(capacity);
+	unwrapped.extend_from_slice(&ring[head..]);
+	unwrapped.extend_from_slice(&ring[..head]);
+	RingDump { bytes: unwrapped, offset }
+}
+
+impl RingDump {
+	/// The bytes in chronological order.
+	#[inline]
+	pub fn bytes(&self) -> &[u8] {
+		&self.bytes
+	}
+	/// Returns the dump of the unwrapped bytes to configure its formatting.
+	#[inline]
+	pub fn dump(&self) -> HexDump<'_> {
+		hexdump(&self.bytes, self.offset)
+	}
+}
+
+impl fmt::Display for RingDump {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		fmt::Display::fmt(&self.dump(), f)
+	}
+}
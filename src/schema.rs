@@ -0,0 +1,32 @@
+use ::std::ops::Range;
+use super::BuildError;
+
+// Parses comma separated `name:size` fields into the byte ranges they cover, eg. `magic:4,version:2,payload:*`.
+// The size `*` takes the rest of the `len` bytes and is only allowed for the last field.
+pub(crate) fn parse(spec: &str, len: usize) -> Result<Vec<(Range<usize>, &str)>, BuildError> {
+	let mut fields = Vec::new();
+	let mut start = 0;
+	let mut rest = false;
+	for (field, item) in spec.split(',').enumerate() {
+		let invalid = BuildError::InvalidSchema { field };
+		let (name, size) = item.split_once(':').ok_or(invalid.clone())?;
+		let name = name.trim();
+		if name.is_empty() || rest {
+			return Err(invalid);
+		}
+		let size = match size.trim() {
+			"*" => {
+				rest = true;
+				len.saturating_sub(start)
+			},
+			size => match size.parse() {
+				Ok(size) if size > 0 => size,
+				_ => return Err(invalid),
+			},
+		};
+		let end = start.checked_add(size).ok_or(invalid)?;
+		fields.push((start..end, name));
+		start = end;
+	}
+	Ok(fields)
+}
@@ -0,0 +1,345 @@
+use ::std::{cmp, error, fmt, mem};
+
+/// Error reversing a hex dump.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ParseError {
+	/// The row on this line, starting at 1, contains something other than hex bytes or has a segment label.
+	InvalidRow { line: usize },
+	/// The reconstructed bytes would exceed the limit.
+	TooLarge { limit: usize },
+}
+impl fmt::Display for ParseError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			ParseError::InvalidRow { line } => write!(f, "invalid row on line {}", line),
+			ParseError::TooLarge { limit } => write!(f, "reconstructed bytes exceed the limit of {} bytes", limit),
+		}
+	}
+}
+impl error::Error for ParseError {}
+
+/// Reconstructs the bytes from a hex dump.
+///
+/// Rows are placed at their address relative to the first row, a `*` line repeats the row before it
+/// up to the next row. Lines which do not start with an address, like headers, rulers and annotations,
+/// are skipped. Escape codes from highlighting are ignored. Rows labeled by segment are invalid.
+///
+/// `max_bytes` limits the size of the reconstructed bytes and is used to pre-size the result.
+///
+/// ```
+/// use hexdump::{hexdump, unhexdump};
+///
+/// let dump = format!("{}", hexdump(b"hexdump", 5));
+/// assert_eq!(unhexdump(&dump, None), Ok(b"hexdump".to_vec()));
+/// ```
+pub fn unhexdump(text: &str, max_bytes: Option<usize>) -> Result<Vec<u8>, ParseError> {
+	let limit = max_bytes.unwrap_or(usize::MAX);
+	// Every byte takes up at least 3 characters in a dump
+	let mut bytes = Vec::with_capacity(cmp::min(limit, text.len() / 3));
+	let mut base = None;
+	let mut prev: Option<Vec<u8>> = None;
+	let mut repeat = false;
+
+	for (i, line) in text.lines().enumerate() {
+		let line = strip_escapes(line);
+		if line.trim() == "*" {
+			repeat = true;
+			continue;
+		}
+		let (address, row) = match parse_row(&line) {
+			Some(Ok(row)) => row,
+			Some(Err(())) => return Err(ParseError::InvalidRow { line: i + 1 }),
+			None => continue,
+		};
+
+		let base = *base.get_or_insert(address);
+		let pos = address.checked_sub(base)
+			.ok_or(ParseError::InvalidRow { line: i + 1 })?;
+		if pos.checked_add(row.len() as u64).is_none_or(|end| end > limit as u64) {
+			return Err(ParseError::TooLarge { limit });
+		}
+
+		let pos = pos as usize;
+
+		// Fill the gap with the repeated row or zeroes
+		match prev {
+			Some(ref prev) if repeat && !prev.is_empty() => {
+				while bytes.len() < pos {
+					let n = cmp::min(prev.len(), pos - bytes.len());
+					bytes.extend_from_slice(&prev[..n]);
+				}
+			},
+			_ => bytes.resize(cmp::max(bytes.len(), pos), 0),
+		}
+		bytes.truncate(pos);
+		bytes.extend_from_slice(&row);
+		prev = Some(row);
+		repeat = false;
+	}
+	Ok(bytes)
+}
+
+/// Formats of the hex dumps understood by `unhexdump_auto`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum DumpFormat {
+	/// Rows of an address and hex bytes like the `Display` output, see `unhexdump`.
+	Rows,
+	/// Plain hex digits without addresses like `xxd -p`, whitespace between the digits is ignored.
+	Plain,
+}
+impl DumpFormat {
+	/// Looks up the format by the name used in a format hint, `hexdump` for rows and `plain` or `xxd-ps` for plain hex.
+	pub fn from_name(name: &str) -> Option<DumpFormat> {
+		match name {
+			"hexdump" => Some(DumpFormat::Rows),
+			"plain" | "xxd-ps" => Some(DumpFormat::Plain),
+			_ => None,
+		}
+	}
+	// Plain hex if every line other than comments only holds hex digits.
+	fn sniff(text: &str) -> DumpFormat {
+		let plain = text.lines()
+			.filter(|line| !line.starts_with('#'))
+			.all(|line| line.chars().all(|c| c.is_ascii_hexdigit() || c.is_whitespace()));
+		if plain { DumpFormat::Plain } else { DumpFormat::Rows }
+	}
+}
+
+/// Returns the name of the format hint on the first line of the dump, eg. `xxd-ps` for `# format: xxd-ps`.
+pub fn format_hint(text: &str) -> Option<&str> {
+	let line = text.lines().next()?;
+	let name = line.strip_prefix('#')?.trim_start().strip_prefix("format:")?;
+	Some(name.trim())
+}
+
+/// Reconstructs the bytes from a hex dump in any of the known formats.
+///
+/// A recognized format hint on the first line, like `# format: xxd-ps`, selects the format.
+/// Without a hint or if the hint is not recognized the format is detected from the text.
+///
+/// ```
+/// use hexdump::unhexdump_auto;
+///
+/// assert_eq!(unhexdump_auto("# format: xxd-ps\n6865 7864\n756d70\n", None), Ok(b"hexdump".to_vec()));
+/// assert_eq!(unhexdump_auto("00000000:  68 65 78 64 75 6D 70  |hexdump|\n", None), Ok(b"hexdump".to_vec()));
+/// ```
+pub fn unhexdump_auto(text: &str, max_bytes: Option<usize>) -> Result<Vec<u8>, ParseError> {
+	let format = format_hint(text)
+		.and_then(DumpFormat::from_name)
+		.unwrap_or_else(|| DumpFormat::sniff(text));
+	match format {
+		DumpFormat::Rows => unhexdump(text, max_bytes),
+		DumpFormat::Plain => unhex_plain(text, max_bytes),
+	}
+}
+
+// Decodes plain hex digits, lines starting with `#` are comments.
+fn unhex_plain(text: &str, max_bytes: Option<usize>) -> Result<Vec<u8>, ParseError> {
+	let limit = max_bytes.unwrap_or(usize::MAX);
+	let mut bytes = Vec::with_capacity(cmp::min(limit, text.len() / 2));
+	let mut high = None;
+	let mut line_no = 0;
+	for (i, line) in text.lines().enumerate() {
+		line_no = i + 1;
+		if line.starts_with('#') {
+			continue;
+		}
+		for c in line.chars().filter(|c| !c.is_whitespace()) {
+			let digit = c.to_digit(16).ok_or(ParseError::InvalidRow { line: line_no })? as u8;
+			match high.take() {
+				Some(high) => {
+					if bytes.len() >= limit {
+						return Err(ParseError::TooLarge { limit });
+					}
+					bytes.push(high << 4 | digit);
+				},
+				None => high = Some(digit),
+			}
+		}
+	}
+	// A byte is missing its second digit
+	if high.is_some() {
+		return Err(ParseError::InvalidRow { line: line_no });
+	}
+	Ok(bytes)
+}
+
+/// Reconstructs the bytes from a hex dump given as a stream of characters.
+///
+/// Like `unhexdump` but decodes the dump one line at a time, only the current line and row are kept in memory.
+/// The bytes are yielded as soon as their row is decoded, a row at an address before the end of the previous
+/// row is an invalid row as its bytes were already yielded. The iterator ends after the first error.
+///
+/// ```
+/// use hexdump::{hexdump, unhexdump_stream};
+///
+/// let dump = format!("{}", hexdump(b"hexdump", 5));
+/// let bytes: Result<Vec<u8>, _> = unhexdump_stream(dump.chars()).collect();
+/// assert_eq!(bytes, Ok(b"hexdump".to_vec()));
+/// ```
+pub fn unhexdump_stream<I: Iterator<Item = char>>(chars: I) -> impl Iterator<Item = Result<u8, ParseError>> {
+	UnhexStream {
+		chars,
+		line: String::new(),
+		line_no: 0,
+		base: None,
+		pos: 0,
+		prev: Vec::new(),
+		repeat: false,
+		fill: 0,
+		fill_at: 0,
+		fill_repeat: false,
+		row: Vec::new(),
+		row_at: 0,
+		done: false,
+	}
+}
+
+struct UnhexStream<I> {
+	chars: I,
+	line: String,
+	line_no: usize,
+	base: Option<u64>,
+	pos: u64, // Number of bytes yielded so far
+	prev: Vec<u8>,
+	repeat: bool,
+	fill: u64, // Bytes of the gap before the row still to yield
+	fill_at: usize,
+	fill_repeat: bool, // The gap is filled by repeating the previous row
+	row: Vec<u8>,
+	row_at: usize,
+	done: bool,
+}
+
+impl<I: Iterator<Item = char>> UnhexStream<I> {
+	fn emit(&mut self, byte: u8) -> Option<Result<u8, ParseError>> {
+		self.pos += 1;
+		Some(Ok(byte))
+	}
+	fn fail(&mut self, err: ParseError) -> Option<Result<u8, ParseError>> {
+		self.done = true;
+		Some(Err(err))
+	}
+}
+
+impl<I: Iterator<Item = char>> Iterator for UnhexStream<I> {
+	type Item = Result<u8, ParseError>;
+	fn next(&mut self) -> Option<Result<u8, ParseError>> {
+		loop {
+			if self.done {
+				return None;
+			}
+			if self.fill > 0 {
+				self.fill -= 1;
+				let byte = if self.fill_repeat { self.prev[self.fill_at % self.prev.len()] } else { 0 };
+				self.fill_at += 1;
+				return self.emit(byte);
+			}
+			if self.row_at < self.row.len() {
+				self.row_at += 1;
+				let byte = self.row[self.row_at - 1];
+				return self.emit(byte);
+			}
+
+			// Read the next line
+			self.line.clear();
+			let mut eof = true;
+			for c in self.chars.by_ref() {
+				eof = false;
+				if c == '\n' {
+					break;
+				}
+				self.line.push(c);
+			}
+			if eof && self.line.is_empty() {
+				self.done = true;
+				return None;
+			}
+			self.line_no += 1;
+
+			let line = strip_escapes(&self.line);
+			if line.trim() == "*" {
+				self.repeat = true;
+				continue;
+			}
+			let (address, row) = match parse_row(&line) {
+				Some(Ok(row)) => row,
+				Some(Err(())) => return self.fail(ParseError::InvalidRow { line: self.line_no }),
+				None => continue,
+			};
+			let base = *self.base.get_or_insert(address);
+			let target = match address.checked_sub(base) {
+				Some(target) if target >= self.pos => target,
+				_ => return self.fail(ParseError::InvalidRow { line: self.line_no }),
+			};
+
+			// Fill the gap with the repeated row or zeroes
+			self.prev = mem::replace(&mut self.row, row);
+			self.row_at = 0;
+			self.fill = target - self.pos;
+			self.fill_at = 0;
+			self.fill_repeat = self.repeat && !self.prev.is_empty();
+			self.repeat = false;
+		}
+	}
+}
+
+// Parses the address and hex bytes of a row, `None` if the line is not a row.
+fn parse_row(line: &str) -> Option<Result<(u64, Vec<u8>), ()>> {
+	let colon = line.find(':')?;
+	let label = &line[..colon];
+	// The segment labels like `.text+0x10` do not say where the segment starts
+	let segment = match label.rfind("+0x") {
+		Some(plus) => plus > 0 && label.len() > plus + 3 && label[plus + 3..].bytes().all(|b| b.is_ascii_hexdigit()),
+		None => false,
+	};
+	if !segment && (label.is_empty() || label.len() > 16 || !label.bytes().all(|b| b.is_ascii_hexdigit())) {
+		return None;
+	}
+	let rest = &line[colon + 1..];
+	let hex = &rest[..rest.find('|').unwrap_or(rest.len())];
+	let mut row = Vec::new();
+	// The fields of a schema are separated by a `·`
+	for token in hex.split(|c: char| c.is_whitespace() || c == '·').filter(|token| !token.is_empty()) {
+		match u8::from_str_radix(token, 16) {
+			Ok(byte) if token.len() == 2 => row.push(byte),
+			_ => return Some(Err(())),
+		}
+	}
+	// Rows have at least one byte, a hex label like `cafe:` is not a row
+	if row.is_empty() {
+		return None;
+	}
+	if segment {
+		return Some(Err(()));
+	}
+	let address = u64::from_str_radix(label, 16).ok()?;
+	Some(Ok((address, row)))
+}
+
+// Removes the ANSI escape sequences used for emphasis and the OSC 8 hyperlinks of the addresses.
+fn strip_escapes(line: &str) -> String {
+	let mut result = String::with_capacity(line.len());
+	let mut chars = line.chars().peekable();
+	while let Some(c) = chars.next() {
+		if c == '\x1b' && chars.peek() == Some(&']') {
+			// Operating system commands end with the string terminator `ESC \` or a bell
+			while let Some(c) = chars.next() {
+				if c == '\x07' || (c == '\x1b' && chars.next_if_eq(&'\\').is_some()) {
+					break;
+				}
+			}
+		}
+		else if c == '\x1b' {
+			for c in chars.by_ref() {
+				if c.is_ascii_alphabetic() {
+					break;
+				}
+			}
+		}
+		else {
+			result.push(c);
+		}
+	}
+	result
+}
@@ -0,0 +1,615 @@
+use ::std::{env, fs, process, thread};
+use ::std::io::Write;
+use ::std::path::PathBuf;
+use ::std::process::{Command, Output, Stdio};
+use ::std::time::Duration;
+
+fn temp_file(name: &str, contents: &[u8]) -> PathBuf {
+	let path = env::temp_dir().join(format!("hexdump-{}-{}", process::id(), name));
+	fs::write(&path, contents).unwrap();
+	path
+}
+
+fn hexdump(args: &[&str], paths: &[&PathBuf]) -> Output {
+	Command::new(env!("CARGO_BIN_EXE_hexdump"))
+		.args(args)
+		.args(paths)
+		.output()
+		.unwrap()
+}
+
+#[test]
+fn concat() {
+	let part1 = temp_file("concat1.bin", b"\x00\x11\x22\x33\x44\x55\x66\x77\x88\x99\xAA\xBB");
+	let part2 = temp_file("concat2.bin", b"hexdump!");
+
+	let output = hexdump(&["--concat"], &[&part1, &part2]);
+	assert!(output.status.success());
+	let stdout = String::from_utf8(output.stdout).unwrap();
+	let rows: Vec<&str> = stdout.lines().skip(2).take(2).collect();
+	assert_eq!(rows, [
+		"00000000:  00 11 22 33 44 55 66 77  88 99 AA BB 68 65 78 64  |..\"3DUfw....hexd|",
+		"00000010:  75 6D 70 21                                       |ump!            |",
+	]);
+
+	let output = hexdump(&["--concat", "--marker"], &[&part1, &part2]);
+	assert!(output.status.success());
+	let stdout = String::from_utf8(output.stdout).unwrap();
+	let rows: Vec<&str> = stdout.lines().skip(2).take(4).collect();
+	assert_eq!(rows, [
+		"00000000:  00 11 22 33 44 55 66 77  88 99 AA BB              |..\"3DUfw....    |",
+		"; ---- next file ----",
+		"0000000C:                                       68 65 78 64  |            hexd|",
+		"00000010:  75 6D 70 21                                       |ump!            |",
+	]);
+
+	// The standard input is one of the files
+	let mut child = Command::new(env!("CARGO_BIN_EXE_hexdump"))
+		.args(["--concat", "--marker"])
+		.arg(&part1)
+		.arg("-")
+		.stdin(Stdio::piped())
+		.stdout(Stdio::piped())
+		.spawn()
+		.unwrap();
+	child.stdin.take().unwrap().write_all(b"hexdump!").unwrap();
+	let output = child.wait_with_output().unwrap();
+	assert!(output.status.success());
+	let piped = String::from_utf8(output.stdout).unwrap();
+	assert_eq!(piped.lines().skip(1).collect::<Vec<_>>(), stdout.lines().skip(1).collect::<Vec<_>>());
+
+	let _ = fs::remove_file(part1);
+	let _ = fs::remove_file(part2);
+}
+
+#[test]
+fn separator() {
+	let part1 = temp_file("separator1.bin", b"abc");
+	let part2 = temp_file("separator2.bin", b"def");
+
+	let output = hexdump(&["--separator", "2"], &[&part1, &part2]);
+	assert!(output.status.success());
+	let stdout = String::from_utf8(output.stdout).unwrap();
+	let lines: Vec<&str> = stdout.lines().collect();
+	assert_eq!(lines.len(), 10);
+	assert_eq!(&lines[4..6], ["", ""]);
+	assert!(lines[6].starts_with("Hex dump for "));
+	assert!(stdout.ends_with("+----------------+\n"));
+
+	let output = hexdump(&["--separator", "0"], &[&part1, &part2]);
+	let stdout = String::from_utf8(output.stdout).unwrap();
+	assert!(stdout.lines().all(|line| !line.is_empty()));
+
+	let _ = fs::remove_file(part1);
+	let _ = fs::remove_file(part2);
+}
+
+#[test]
+fn rule_every() {
+	let bytes: Vec<u8> = (0..160).collect();
+	let file = temp_file("rule-every.bin", &bytes);
+
+	let output = hexdump(&["--rule-every", "4"], &[&file]);
+	assert!(output.status.success());
+	let stdout = String::from_utf8(output.stdout).unwrap();
+	let lines: Vec<&str> = stdout.lines().collect();
+	assert_eq!(lines.len(), 15);
+	let rulers: Vec<usize> = (0..lines.len()).filter(|&i| lines[i] == lines[1]).collect();
+	assert_eq!(rulers, [1, 6, 11, 14]);
+	assert!(lines[7].starts_with("00000040:"));
+
+	let output = hexdump(&[], &[&file]);
+	assert_eq!(String::from_utf8(output.stdout).unwrap().lines().count(), 13);
+
+	let _ = fs::remove_file(file);
+}
+
+#[test]
+fn chunk() {
+	let file = temp_file("chunk.bin", b"\x01\x00\x00\x00\x02\x00\x03\x00ABCD\xFF\xFF\x10\x00");
+
+	let output = hexdump(&["--chunk", "u32le,u16le,u16le"], &[&file]);
+	assert!(output.status.success());
+	let stdout = String::from_utf8(output.stdout).unwrap();
+	let lines: Vec<&str> = stdout.lines().collect();
+	assert_eq!(lines[2..4], [
+		"00000000:  01 00 00 00 02 00 03 00  |........|  u32le=1 u16le=2 u16le=3",
+		"00000008:  41 42 43 44 FF FF 10 00  |ABCD....|  u32le=1145258561 u16le=65535 u16le=16",
+	]);
+	assert_eq!(lines.len(), 5);
+
+	let output = hexdump(&["-n", "10", "--chunk", "u32le,u16le,u16le"], &[&file]);
+	let stdout = String::from_utf8(output.stdout).unwrap();
+	assert_eq!(stdout.lines().nth(3), Some("00000008:  41 42                    |AB      |"));
+
+	let output = hexdump(&["--chunk", "u32le,u24le"], &[&file]);
+	assert!(!output.status.success());
+
+	// The rulers are as wide as the records
+	let bytes: Vec<u8> = (0x40..0x80).collect();
+	let wide = temp_file("chunk-wide.bin", &bytes);
+	for layout in &["u64le", "u64le,u64le,u64le,u64le"] {
+		let output = hexdump(&["--chunk", layout], &[&wide]);
+		assert!(output.status.success());
+		let stdout = String::from_utf8(output.stdout).unwrap();
+		let lines: Vec<&str> = stdout.lines().collect();
+		assert_eq!(lines[1], lines[lines.len() - 1]);
+		assert_eq!(lines[1].find('+'), lines[2].find('|'));
+		let row = &lines[2][..lines[2].rfind('|').unwrap() + 1];
+		assert_eq!(lines[1].len(), row.len());
+	}
+	let _ = fs::remove_file(wide);
+
+	let _ = fs::remove_file(file);
+}
+
+#[test]
+fn diff() {
+	// Large enough to not be read in a single block
+	let old: Vec<u8> = (0..0x100000u32).map(|i| (i % 251) as u8).collect();
+	let mut new = old.clone();
+	new[0xFFFF4] = 0xFF;
+	new.extend_from_slice(b"tail");
+	let old_file = temp_file("diff-old.bin", &old);
+	let new_file = temp_file("diff-new.bin", &new);
+
+	let output = hexdump(&["--diff"], &[&old_file, &new_file]);
+	assert!(output.status.success());
+	let stdout = String::from_utf8(output.stdout).unwrap();
+	let lines: Vec<&str> = stdout.lines().collect();
+	assert_eq!(lines[1], "  --------:----------------------------------------------------+----------------+");
+	assert_eq!(lines[lines.len() - 1], lines[1]);
+	assert_eq!(lines[2..lines.len() - 1], [
+		"; ---- 1048560 matching bytes ----",
+		"- 000FFFF0:  85 86 87 88 89 8A 8B 8C  8D 8E 8F 90 91 92 93 94  |................|",
+		"+ 000FFFF0:  85 86 87 88 FF 8A 8B 8C  8D 8E 8F 90 91 92 93 94  |................|",
+		"+ 00100000:  74 61 69 6C                                       |tail            |",
+	]);
+
+	// The range options apply to both files
+	let output = hexdump(&["--diff", "--goto", "FFFF2", "-n", "4"], &[&old_file, &new_file]);
+	assert!(output.status.success());
+	let stdout = String::from_utf8(output.stdout).unwrap();
+	assert_eq!(stdout.lines().skip(2).take(2).collect::<Vec<_>>(), [
+		"- 000FFFF2:        87 88 89 8A                                 |  ....          |",
+		"+ 000FFFF2:        87 88 FF 8A                                 |  ....          |",
+	]);
+	let output = hexdump(&["--diff", "-s", "16", "-n", "256"], &[&old_file, &new_file]);
+	assert_eq!(String::from_utf8(output.stdout).unwrap().lines().nth(2), Some("; ---- 256 matching bytes ----"));
+	// As do the colors
+	let output = hexdump(&["--diff", "--goto", "FFFF2", "-n", "4", "--color"], &[&old_file, &new_file]);
+	assert!(String::from_utf8(output.stdout).unwrap().lines().nth(2).unwrap().contains("\x1b[38;2;"));
+	// The other output formats are rejected
+	for flags in &[&["-t", "16"][..], &["--concat"], &["--raw"], &["--format", "rust-array"], &["--chunk", "u32le"], &["--json-lines"]] {
+		let output = hexdump(&[&["--diff"][..], flags].concat(), &[&old_file, &new_file]);
+		assert!(!output.status.success(), "{:?}", flags);
+		assert!(String::from_utf8(output.stderr).unwrap().contains(flags[0]));
+	}
+
+	let output = hexdump(&["--diff"], &[&old_file]);
+	assert!(!output.status.success());
+
+	let _ = fs::remove_file(old_file);
+	let _ = fs::remove_file(new_file);
+}
+
+// Parses a flat JSON object of numbers and strings, `None` if the line is not valid JSON of that shape.
+fn parse_json_object(line: &str) -> Option<Vec<(String, String)>> {
+	fn string(chars: &mut ::std::iter::Peekable<::std::str::Chars>) -> Option<String> {
+		if chars.next()? != '"' {
+			return None;
+		}
+		let mut s = String::new();
+		loop {
+			match chars.next()? {
+				'"' => return Some(s),
+				'\\' => match chars.next()? {
+					'"' => s.push('"'),
+					'\\' => s.push('\\'),
+					'/' => s.push('/'),
+					'n' => s.push('\n'),
+					't' => s.push('\t'),
+					'u' => {
+						let hex: String = (0..4).map(|_| chars.next()).collect::<Option<_>>()?;
+						s.push(char::from_u32(u32::from_str_radix(&hex, 16).ok()?)?);
+					},
+					_ => return None,
+				},
+				c if (c as u32) < 0x20 => return None,
+				c => s.push(c),
+			}
+		}
+	}
+	let mut chars = line.chars().peekable();
+	let mut fields = Vec::new();
+	if chars.next()? != '{' {
+		return None;
+	}
+	loop {
+		let key = string(&mut chars)?;
+		if chars.next()? != ':' {
+			return None;
+		}
+		let value = if chars.peek() == Some(&'"') { string(&mut chars)? } else {
+			let mut number = String::new();
+			while let Some(&c) = chars.peek().filter(|c| c.is_ascii_digit()) {
+				number.push(c);
+				chars.next();
+			}
+			if number.is_empty() {
+				return None;
+			}
+			number
+		};
+		fields.push((key, value));
+		match chars.next()? {
+			',' => continue,
+			'}' if chars.next().is_none() => return Some(fields),
+			_ => return None,
+		}
+	}
+}
+
+#[test]
+fn json_lines() {
+	let file = temp_file("json-lines.bin", b"\x00\x11\"quoted\" \\path\\ \x7F\xFF and more text");
+
+	let output = hexdump(&["--json-lines", "-s", "1"], &[&file]);
+	assert!(output.status.success());
+	let stdout = String::from_utf8(output.stdout).unwrap();
+	let rows: Vec<_> = stdout.lines().map(|line| parse_json_object(line).unwrap_or_else(|| panic!("invalid JSON: {}", line))).collect();
+	assert_eq!(rows.len(), 3);
+	assert_eq!(rows[0], [
+		("offset".to_owned(), "1".to_owned()),
+		("hex".to_owned(), "11 22 71 75 6F 74 65 64 22 20 5C 70 61 74 68".to_owned()),
+		("ascii".to_owned(), ".\"quoted\" \\path".to_owned()),
+	]);
+	assert_eq!(rows[1][0].1, "16");
+	// The DEL glyph is a control character escaped as `\u007f`
+	assert!(stdout.lines().nth(1).unwrap().contains(r#""ascii":"\\ \u007f. and more te""#));
+	assert_eq!(rows[1][2].1, "\\ \u{7f}. and more te");
+
+	let _ = fs::remove_file(file);
+}
+
+#[test]
+fn count_bytes() {
+	let bytes: Vec<u8> = (0..160).collect();
+	let file = temp_file("count-bytes.bin", &bytes);
+
+	let output = hexdump(&["--rule-every", "5", "--count-bytes"], &[&file]);
+	assert!(output.status.success());
+	let stdout = String::from_utf8(output.stdout).unwrap();
+	let lines: Vec<&str> = stdout.lines().collect();
+	assert_eq!(lines[7], lines[1]);
+	assert_eq!(lines[8], "; 50% (80/160)");
+	assert!(lines[9].starts_with("00000050:"));
+
+	// Without rulers the progress is noted every 16 rows
+	let large = temp_file("count-bytes-large.bin", &[0x55u8; 0x400]);
+	let output = hexdump(&["--count-bytes"], &[&large]);
+	let stdout = String::from_utf8(output.stdout).unwrap();
+	let notes: Vec<&str> = stdout.lines().filter(|line| line.starts_with("; ")).collect();
+	assert_eq!(notes, ["; 25% (256/1024)", "; 50% (512/1024)", "; 75% (768/1024)"]);
+
+	// The rows are counted across the file boundaries
+	let part1 = temp_file("count-bytes1.bin", &[0x11u8; 0x18]);
+	let part2 = temp_file("count-bytes2.bin", &[0x22u8; 0x110]);
+	let output = hexdump(&["--concat", "--marker", "--count-bytes"], &[&part1, &part2]);
+	assert!(output.status.success());
+	let stdout = String::from_utf8(output.stdout).unwrap();
+	let lines: Vec<&str> = stdout.lines().collect();
+	let note = lines.iter().position(|line| line.starts_with("; ") && line.ends_with("/296)")).unwrap();
+	assert_eq!(lines[note], "; 81% (240/296)");
+	assert!(lines[note + 1].starts_with("000000F0:"));
+	assert_eq!(lines.iter().filter(|line| line.ends_with("/296)")).count(), 1);
+
+	let _ = fs::remove_file(file);
+	let _ = fs::remove_file(large);
+	let _ = fs::remove_file(part1);
+	let _ = fs::remove_file(part2);
+}
+
+#[test]
+fn raw() {
+	let file = temp_file("raw.bin", b"\x00\x11\x22\x33\x44\x55\x66\x77\x88\x99");
+
+	let output = hexdump(&["--raw", "-s", "4", "-n", "4"], &[&file]);
+	assert!(output.status.success());
+	assert_eq!(output.stdout, b"\x44\x55\x66\x77");
+
+	let _ = fs::remove_file(file);
+}
+
+#[test]
+fn format() {
+	let file = temp_file("format.bin", b"\x00\x11\x22\xAB\xFF");
+
+	let output = hexdump(&["--format", "rust-array"], &[&file]);
+	assert!(output.status.success());
+	assert_eq!(String::from_utf8(output.stdout).unwrap(), "pub const DATA: [u8; 5] = [\n    0x00, 0x11, 0x22, 0xab, 0xff,\n];\n");
+
+	let output = hexdump(&["--format", "c-embed"], &[&file]);
+	assert!(output.status.success());
+	assert_eq!(String::from_utf8(output.stdout).unwrap(), "0x00, 0x11, 0x22, 0xab, 0xff\n");
+
+	let output = hexdump(&["--format", "c-embed", "-n", "4"], &[&file]);
+	assert_eq!(String::from_utf8(output.stdout).unwrap(), "0x00, 0x11, 0x22, 0xab\n");
+
+	let output = hexdump(&["--format", "json"], &[&file]);
+	assert!(!output.status.success());
+
+	let _ = fs::remove_file(file);
+}
+
+#[test]
+fn ascii_charset() {
+	let file = temp_file("charset.bin", b"caf\xE9");
+
+	let output = hexdump(&["--ascii-charset", "latin1"], &[&file]);
+	assert!(output.status.success());
+	let stdout = String::from_utf8(output.stdout).unwrap();
+	assert_eq!(stdout.lines().nth(2), Some("00000000:  63 61 66 E9                                       |café            |"));
+
+	let output = hexdump(&["--ascii-charset", "klingon"], &[&file]);
+	assert!(!output.status.success());
+
+	let _ = fs::remove_file(file);
+}
+
+#[test]
+fn edit_without_editor() {
+	let file = temp_file("edit.bin", b"hexdump");
+
+	let output = Command::new(env!("CARGO_BIN_EXE_hexdump"))
+		.env_remove("EDITOR")
+		.arg("-e")
+		.arg(&file)
+		.output()
+		.unwrap();
+	assert!(output.status.success());
+	let stdout = String::from_utf8(output.stdout).unwrap();
+	let dump = fs::read_to_string(stdout.trim_end()).unwrap();
+	assert_eq!(dump.lines().nth(2), Some("00000000:  68 65 78 64 75 6D 70                              |hexdump         |"));
+
+	let _ = fs::remove_file(stdout.trim_end());
+	let _ = fs::remove_file(file);
+}
+
+#[test]
+fn edit_removes_temp_file() {
+	let file = temp_file("edit-removes.bin", b"hexdump");
+	let dir = env::temp_dir().join(format!("hexdump-{}-edit-dir", process::id()));
+	fs::create_dir_all(&dir).unwrap();
+
+	let edit = |editor: &str, path: &PathBuf| Command::new(env!("CARGO_BIN_EXE_hexdump"))
+		.env("TMPDIR", &dir)
+		.env("EDITOR", editor)
+		.arg("-e")
+		.arg(path)
+		.output()
+		.unwrap();
+	let output = edit("cat", &file);
+	assert!(output.status.success());
+	let stdout = String::from_utf8(output.stdout).unwrap();
+	assert_eq!(stdout.lines().nth(2), Some("00000000:  68 65 78 64 75 6D 70                              |hexdump         |"));
+	assert_eq!(fs::read_dir(&dir).unwrap().count(), 0);
+
+	// Neither a failing editor nor a failing dump leave the temporary file behind
+	assert!(!edit("false", &file).status.success());
+	assert_eq!(fs::read_dir(&dir).unwrap().count(), 0);
+	assert!(!edit("cat", &dir.join("missing.bin")).status.success());
+	assert_eq!(fs::read_dir(&dir).unwrap().count(), 0);
+
+	let _ = fs::remove_dir_all(dir);
+	let _ = fs::remove_file(file);
+}
+
+#[test]
+fn reverse_max_bytes() {
+	let file = temp_file("reverse.txt", b"Hex dump for \"reverse.bin\":\n\
+		00000000:  68 65 78 64 75 6D 70 00  01 02 03                 |hexdump....     |\n");
+
+	let output = hexdump(&["-r"], &[&file]);
+	assert!(output.status.success());
+	assert_eq!(output.stdout, b"hexdump\x00\x01\x02\x03");
+
+	let output = hexdump(&["-r", "--max-bytes", "11"], &[&file]);
+	assert!(output.status.success());
+
+	let output = hexdump(&["-r", "--max-bytes", "10"], &[&file]);
+	assert!(!output.status.success());
+	assert!(output.stdout.is_empty());
+
+	let _ = fs::remove_file(file);
+}
+
+#[test]
+fn reverse_format_hint() {
+	let file = temp_file("reverse-hint.txt", b"# format: xxd-ps\n00000000\n6865 7864 756d 70\n");
+	let output = hexdump(&["-r"], &[&file]);
+	assert!(output.status.success());
+	assert_eq!(output.stdout, b"\x00\x00\x00\x00hexdump");
+	let _ = fs::remove_file(file);
+
+	let file = temp_file("reverse-unknown-hint.txt", b"# format: klingon\n68 65 78 64 75 6d 70\n");
+	let output = hexdump(&["-r"], &[&file]);
+	assert!(output.status.success());
+	assert_eq!(output.stdout, b"hexdump");
+	assert!(String::from_utf8(output.stderr).unwrap().contains("unknown format \"klingon\""));
+	let _ = fs::remove_file(file);
+}
+
+#[test]
+fn reverse_output() {
+	let file = temp_file("reverse-output.txt", b"00000000:  68 65 78 64 75 6D 70  |hexdump|\n");
+	let output_path = env::temp_dir().join(format!("hexdump-{}-reverse-output.bin", process::id()));
+
+	let output = hexdump(&["-r", "-o", output_path.to_str().unwrap()], &[&file]);
+	assert!(output.status.success());
+	assert!(output.stdout.is_empty());
+	assert_eq!(fs::read(&output_path).unwrap(), b"hexdump");
+
+	// Run with a terminal as the output if `script` is available to provide one
+	let command = |flags: &str| format!("{} -r {} {}", env!("CARGO_BIN_EXE_hexdump"), flags, file.display());
+	if let Ok(output) = Command::new("script").args(["-qec", &command(""), "/dev/null"]).output() {
+		assert!(!output.status.success());
+		let output = Command::new("script").args(["-qec", &command("--force"), "/dev/null"]).output().unwrap();
+		assert!(output.status.success());
+		assert!(output.stdout.ends_with(b"hexdump"));
+		// Watching writes to the terminal as well, the timeout ends it should it start watching
+		let watching = format!("timeout 5 {}", command("--watch"));
+		let output = Command::new("script").args(["-qec", &watching, "/dev/null"]).output().unwrap();
+		assert!(String::from_utf8_lossy(&output.stdout).contains("refusing to write binary"));
+	}
+
+	let _ = fs::remove_file(output_path);
+	let _ = fs::remove_file(file);
+}
+
+#[test]
+fn length_from() {
+	let file = temp_file("length-from.bin", b"\x00\x11\x22\x33\x44\x55\x66\x77\x88\x99");
+	let reference = temp_file("length-from.ref", b"abc");
+
+	let output = hexdump(&["-s", "2", "--length-from", reference.to_str().unwrap()], &[&file]);
+	assert!(output.status.success());
+	let stdout = String::from_utf8(output.stdout).unwrap();
+	assert_eq!(stdout.lines().nth(2), Some("00000002:        22 33 44                                    |  \"3D           |"));
+
+	let missing = env::temp_dir().join("hexdump-length-from.missing");
+	let output = hexdump(&["--length-from", missing.to_str().unwrap()], &[&file]);
+	assert!(!output.status.success());
+
+	let _ = fs::remove_file(file);
+	let _ = fs::remove_file(reference);
+}
+
+#[test]
+fn goto() {
+	let mut bytes = vec![0u8; 0x4000];
+	bytes[0x2000..0x2010].copy_from_slice(b"a known struct!!");
+	let file = temp_file("goto.bin", &bytes);
+
+	let output = hexdump(&["--goto", "0x2000", "-n", "16"], &[&file]);
+	assert!(output.status.success());
+	let stdout = String::from_utf8(output.stdout).unwrap();
+	assert_eq!(stdout.lines().nth(2), Some("00002000:  61 20 6B 6E 6F 77 6E 20  73 74 72 75 63 74 21 21  |a known struct!!|"));
+	assert_eq!(stdout.lines().count(), 4);
+
+	let output = hexdump(&["--goto", "200G"], &[&file]);
+	assert!(!output.status.success());
+	let output = hexdump(&["-s", "16", "--goto", "0x2000"], &[&file]);
+	assert!(!output.status.success());
+	assert_eq!(String::from_utf8(output.stderr).unwrap(), "hexdump: --goto: cannot be combined with -s.\n");
+
+	let _ = fs::remove_file(file);
+}
+
+#[test]
+fn tail() {
+	let bytes: Vec<u8> = (0..40).collect();
+	let file = temp_file("tail.bin", &bytes);
+
+	let output = hexdump(&["-t", "16"], &[&file]);
+	assert!(output.status.success());
+	let stdout = String::from_utf8(output.stdout).unwrap();
+	let rows: Vec<&str> = stdout.lines().skip(2).take(2).collect();
+	assert_eq!(rows, [
+		"00000018:                           18 19 1A 1B 1C 1D 1E 1F  |        ........|",
+		"00000020:  20 21 22 23 24 25 26 27                           | !\"#$%&'        |",
+	]);
+
+	// Standard input cannot seek, the tail is kept in a ring buffer
+	let mut child = Command::new(env!("CARGO_BIN_EXE_hexdump"))
+		.args(["--tail", "16", "-"])
+		.stdin(Stdio::piped())
+		.stdout(Stdio::piped())
+		.spawn()
+		.unwrap();
+	child.stdin.take().unwrap().write_all(&bytes).unwrap();
+	let output = child.wait_with_output().unwrap();
+	assert!(output.status.success());
+	assert_eq!(String::from_utf8(output.stdout).unwrap(), stdout.replace(file.to_str().unwrap(), "-"));
+
+	let output = hexdump(&["-t", "100", "--raw"], &[&file]);
+	assert_eq!(output.stdout, bytes);
+
+	// A tail larger than the input keeps all of it
+	let mut child = Command::new(env!("CARGO_BIN_EXE_hexdump"))
+		.args(["-t", "100000000000000", "--raw", "-"])
+		.stdin(Stdio::piped())
+		.stdout(Stdio::piped())
+		.spawn()
+		.unwrap();
+	child.stdin.take().unwrap().write_all(&bytes).unwrap();
+	let output = child.wait_with_output().unwrap();
+	assert!(output.status.success());
+	assert_eq!(output.stdout, bytes);
+
+	let _ = fs::remove_file(file);
+}
+
+#[test]
+fn watch() {
+	let file = temp_file("watch.bin", b"hexdump");
+
+	// Without watching the dump is written once
+	let output = hexdump(&[], &[&file]);
+	assert!(output.status.success());
+	let once = String::from_utf8(output.stdout).unwrap();
+	assert!(once.starts_with("Hex dump for "));
+
+	let mut child = Command::new(env!("CARGO_BIN_EXE_hexdump"))
+		.args(["--watch"])
+		.arg(&file)
+		.stdout(Stdio::piped())
+		.spawn()
+		.unwrap();
+	thread::sleep(Duration::from_millis(300));
+	child.kill().unwrap();
+	let output = child.wait_with_output().unwrap();
+	let stdout = String::from_utf8(output.stdout).unwrap();
+	let (header, dump) = stdout.split_once('\n').unwrap();
+	assert!(header.starts_with("; ---- ") && header.ends_with(" UTC ----"));
+	assert_eq!(dump, once);
+
+	let _ = fs::remove_file(file);
+}
+
+#[test]
+fn theme() {
+	let file = temp_file("theme.bin", b"\x00A");
+
+	let output = hexdump(&["--theme", "solarized", "--color-zero", "0,0,128"], &[&file]);
+	assert!(output.status.success());
+	let stdout = String::from_utf8(output.stdout).unwrap();
+	assert!(stdout.lines().nth(2).unwrap().starts_with("00000000:  \x1b[38;2;0;0;128m00\x1b[39m \x1b[38;2;38;139;210m41\x1b[39m "));
+
+	let output = hexdump(&["--color-zero", "0,0"], &[&file]);
+	assert!(!output.status.success());
+
+	let _ = fs::remove_file(file);
+}
+
+#[cfg(feature = "sha256")]
+#[test]
+fn expect_sha256() {
+	let file = temp_file("expect-sha256.bin", b"hexdump");
+
+	let output = hexdump(&["--expect-sha256", "9891f67674e9d6bc48c300dc6efd17c4ca24007d20e33c7de6025d4bbdd8baa4"], &[&file]);
+	assert!(output.status.success());
+
+	let output = hexdump(&["--expect-sha256", "9891F67674E9D6BC48C300DC6EFD17C4CA24007D20E33C7DE6025D4BBDD8BAA5"], &[&file]);
+	assert!(!output.status.success());
+	// The dump is still written before the verification fails
+	let stdout = String::from_utf8(output.stdout).unwrap();
+	assert_eq!(stdout.lines().nth(2), Some("00000000:  68 65 78 64 75 6D 70                              |hexdump         |"));
+	let stderr = String::from_utf8(output.stderr).unwrap();
+	assert!(stderr.contains("sha256 mismatch"));
+
+	let output = hexdump(&["--expect-sha256", "9891"], &[&file]);
+	assert!(!output.status.success());
+
+	let _ = fs::remove_file(file);
+}